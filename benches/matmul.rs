@@ -0,0 +1,82 @@
+// Compares the blocked products with the original triple loop. Run with `cargo bench`, or add
+// `--features matrixmultiply` and/or `--features parallel` to measure those backends instead.
+
+use std::time::{Duration, Instant};
+
+use neuralnet::Matrix;
+use rand::{rngs::StdRng, SeedableRng};
+
+const SIZES: [usize; 3] = [256, 512, 1024];
+
+// The implementation dot_multiply used before the kernels were blocked
+fn naive_dot_multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut buffer = vec![0.0; a.rows * b.cols];
+
+    for a_r in 0..a.rows {
+        for b_c in 0..b.cols {
+            let mut sum = 0.0;
+            for a_c in 0..a.cols {
+                sum += a.data[a_r * a.cols + a_c] * b.data[a_c * b.cols + b_c];
+            }
+            buffer[a_r * b.cols + b_c] = sum;
+        }
+    }
+
+    Matrix::from_vec(&buffer, a.rows, b.cols)
+}
+
+fn time<F: FnMut() -> Matrix>(mut f: F) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..3 {
+        let start = Instant::now();
+        std::hint::black_box(f());
+        best = best.min(start.elapsed());
+    }
+    best
+}
+
+fn report(name: &str, size: usize, baseline: Duration, optimized: Duration) {
+    let backend = match (cfg!(feature = "matrixmultiply"), cfg!(feature = "parallel")) {
+        (true, true) => "matrixmultiply+rayon",
+        (true, false) => "matrixmultiply",
+        (false, true) => "blocked+rayon",
+        (false, false) => "blocked",
+    };
+    println!(
+        "{:<16} {:>5}  naive {:>10.2?}  {} {:>10.2?}  speedup {:>5.1}x",
+        name,
+        size,
+        baseline,
+        backend,
+        optimized,
+        baseline.as_secs_f64() / optimized.as_secs_f64()
+    );
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for size in SIZES {
+        let a: Matrix = Matrix::random(size, size, &mut rng);
+        let b: Matrix = Matrix::random(size, size, &mut rng);
+
+        report(
+            "dot_multiply",
+            size,
+            time(|| naive_dot_multiply(&a, &b)),
+            time(|| a.dot_multiply(&b)),
+        );
+        report(
+            "dot_transposed",
+            size,
+            time(|| naive_dot_multiply(&a, &b.transpose())),
+            time(|| a.dot_transposed(&b)),
+        );
+        report(
+            "transposed_dot",
+            size,
+            time(|| naive_dot_multiply(&a.transpose(), &b)),
+            time(|| a.transposed_dot(&b)),
+        );
+    }
+}
@@ -1,15 +1,43 @@
-use std::f64::consts::E;
+use crate::float::Float;
 
 #[derive(Clone)]
-pub struct Activation {
-    pub function: &'static dyn Fn(f64) -> f64,
-    pub derivative: &'static dyn Fn(f64) -> f64,
+pub struct Activation<T = f64> {
+    pub name: &'static str,
+    pub function: fn(T) -> T,
+    pub derivative: fn(T) -> T,
 }
 
-pub const SIGMOID: Activation = Activation {
-    function: &|x| 1.0 / (1.0 + E.powf(-x)),
-    derivative: &|x| x * (1.0 - x),
-};
+fn sigmoid<T: Float>(x: T) -> T {
+    T::one() / (T::one() + (-x).exp())
+}
+
+// Expressed in terms of the activation's output, which is what back propagation has at hand
+fn sigmoid_derivative<T: Float>(x: T) -> T {
+    x * (T::one() - x)
+}
+
+impl<T: Float> Activation<T> {
+    pub const SIGMOID: Activation<T> = Activation {
+        name: "sigmoid",
+        function: sigmoid::<T>,
+        derivative: sigmoid_derivative::<T>,
+    };
+}
+
+pub const SIGMOID: Activation<f64> = Activation::SIGMOID;
+
+impl<T> std::fmt::Debug for Activation<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Activation({})", self.name)
+    }
+}
+
+pub fn from_name<T: Float>(name: &str) -> Option<Activation<T>> {
+    match name {
+        "sigmoid" => Some(Activation::SIGMOID),
+        _ => None,
+    }
+}
 
 #[test]
 fn test() {
@@ -0,0 +1,154 @@
+use std::{
+    fmt::{Debug, Display},
+    iter::Sum,
+    ops::{AddAssign, DivAssign, MulAssign, SubAssign},
+};
+
+use rand::{distributions::uniform::SampleUniform, Rng};
+use rand_distr::StandardNormal;
+use serde::{de::DeserializeOwned, Serialize};
+
+// Numeric type shared by the matrices, networks and NEAT genes. Implemented for f32 and f64 only,
+// sealed so methods that depend on features, like gemm, can be added without breaking other impls.
+pub trait Float:
+    sealed::Sealed
+    + num_traits::Float
+    + num_traits::FromPrimitive
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Sum
+    + Default
+    + Debug
+    + Display
+    + SampleUniform
+    + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
+    + 'static
+{
+    // Number of representable values between self and other, u64::MAX when either is NaN
+    fn ulps(self, other: Self) -> u64;
+
+    // c (m x n) += a (m x k) * b (k x n) through matrixmultiply. Every operand is described by its
+    // (row, column) strides, so transposed operands need no copy.
+    #[cfg(feature = "matrixmultiply")]
+    fn gemm(
+        shape: (usize, usize, usize),
+        a: &[Self],
+        a_strides: (usize, usize),
+        b: &[Self],
+        b_strides: (usize, usize),
+        c: &mut [Self],
+        c_strides: (usize, usize),
+    );
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_float {
+    ($float:ty, $bits:ty, $gemm:ident) => {
+        impl sealed::Sealed for $float {}
+
+        impl Float for $float {
+            fn ulps(self, other: Self) -> u64 {
+                if self.is_nan() || other.is_nan() {
+                    return u64::MAX;
+                }
+
+                // Reorders the sign-magnitude bit patterns so adjacent floats are adjacent integers
+                let ordered = |x: $float| {
+                    let bits = x.to_bits() as $bits;
+                    let ordered = if bits < 0 { <$bits>::MIN - bits } else { bits };
+                    ordered as i128
+                };
+                (ordered(self) - ordered(other)).unsigned_abs() as u64
+            }
+
+            #[cfg(feature = "matrixmultiply")]
+            fn gemm(
+                (m, k, n): (usize, usize, usize),
+                a: &[Self],
+                a_strides: (usize, usize),
+                b: &[Self],
+                b_strides: (usize, usize),
+                c: &mut [Self],
+                c_strides: (usize, usize),
+            ) {
+                check_strides(a.len(), m, k, a_strides);
+                check_strides(b.len(), k, n, b_strides);
+                check_strides(c.len(), m, n, c_strides);
+
+                // Safety: the checks above keep every strided access inside the slices
+                unsafe {
+                    matrixmultiply::$gemm(
+                        m,
+                        k,
+                        n,
+                        1.0,
+                        a.as_ptr(),
+                        a_strides.0 as isize,
+                        a_strides.1 as isize,
+                        b.as_ptr(),
+                        b_strides.0 as isize,
+                        b_strides.1 as isize,
+                        1.0,
+                        c.as_mut_ptr(),
+                        c_strides.0 as isize,
+                        c_strides.1 as isize,
+                    );
+                }
+            }
+        }
+    };
+}
+
+impl_float!(f32, i32, sgemm);
+impl_float!(f64, i64, dgemm);
+
+#[cfg(feature = "matrixmultiply")]
+fn check_strides(len: usize, rows: usize, cols: usize, (row_stride, col_stride): (usize, usize)) {
+    if rows > 0 && cols > 0 && (rows - 1) * row_stride + (cols - 1) * col_stride >= len {
+        panic!(
+            "Strided {}x{} operand does not fit in {} elements",
+            rows, cols, len
+        );
+    }
+}
+
+// Converts an f64 constant into any Float, which is always representable (possibly rounded)
+pub fn cast<T: Float>(x: f64) -> T {
+    T::from_f64(x).unwrap()
+}
+
+pub fn uniform<T: Float, R: Rng + ?Sized>(rng: &mut R, low: T, high: T) -> T {
+    rng.gen_range(low..high)
+}
+
+// Sampled in f64 and rounded, so f32 draws come from the same stream as f64 ones
+pub fn normal<T: Float, R: Rng + ?Sized>(rng: &mut R, mean: T, std: T) -> T {
+    let z: f64 = rng.sample(StandardNormal);
+    mean + std * cast(z)
+}
+
+#[test]
+fn cast_precision() {
+    assert_eq!(cast::<f64>(0.1), 0.1);
+    assert_eq!(cast::<f32>(0.1), 0.1f32);
+}
+
+#[test]
+fn ulps() {
+    let next = f64::from_bits(1.0f64.to_bits() + 1);
+
+    assert_eq!(1.0f64.ulps(1.0), 0);
+    assert_eq!(1.0f64.ulps(next), 1);
+    assert_eq!(0.0f64.ulps(-0.0), 0);
+    assert_eq!(f64::from_bits(1).ulps(-f64::from_bits(1)), 2);
+    assert_eq!(1.0f32.ulps(f32::from_bits(1.0f32.to_bits() + 3)), 3);
+    assert_eq!(f64::NAN.ulps(1.0), u64::MAX);
+}
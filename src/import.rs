@@ -0,0 +1,472 @@
+use std::{fs, fs::File, io::Read, path::Path};
+
+use safetensors::{Dtype, SafeTensors};
+use zip::ZipArchive;
+
+use crate::{
+    float::{self, Float},
+    matrix::Matrix,
+    network::Network,
+};
+
+// Arrays are looked up by these names in .npz and safetensors files, e.g.
+// `np.savez(path, weights_0=w0, biases_0=b0, ...)` with weights shaped [outputs, inputs]
+const WEIGHTS: &str = "weights";
+const BIASES: &str = "biases";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layout {
+    RowMajor,
+    ColumnMajor,
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(std::io::Error),
+    Zip(zip::result::ZipError),
+    SafeTensors(safetensors::SafeTensorError),
+    Format(String),
+    UnsupportedDtype(String),
+    MissingArray(String),
+    LayerCount {
+        expected: usize,
+        found: usize,
+    },
+    ShapeMismatch {
+        name: String,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ImportError::Io(error) => write!(f, "Could not read weights: {}", error),
+            ImportError::Zip(error) => write!(f, "Invalid npz archive: {}", error),
+            ImportError::SafeTensors(error) => write!(f, "Invalid safetensors file: {}", error),
+            ImportError::Format(message) => write!(f, "Invalid npy data: {}", message),
+            ImportError::UnsupportedDtype(dtype) => {
+                write!(f, "Unsupported dtype '{}', expected f32 or f64", dtype)
+            }
+            ImportError::MissingArray(name) => write!(f, "Array '{}' not found", name),
+            ImportError::LayerCount { expected, found } => {
+                write!(
+                    f,
+                    "Expected arrays for {} layers, found {}",
+                    expected, found
+                )
+            }
+            ImportError::ShapeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Array '{}' has shape {:?}, expected {:?}",
+                name, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<std::io::Error> for ImportError {
+    fn from(error: std::io::Error) -> Self {
+        ImportError::Io(error)
+    }
+}
+
+impl From<zip::result::ZipError> for ImportError {
+    fn from(error: zip::result::ZipError) -> Self {
+        ImportError::Zip(error)
+    }
+}
+
+impl From<safetensors::SafeTensorError> for ImportError {
+    fn from(error: safetensors::SafeTensorError) -> Self {
+        ImportError::SafeTensors(error)
+    }
+}
+
+struct Array {
+    shape: Vec<usize>,
+    data: Vec<f64>,
+}
+
+impl Array {
+    // Reorders column-major data so `data` is always row-major
+    fn new(shape: Vec<usize>, data: Vec<f64>, layout: Layout) -> Self {
+        if layout == Layout::RowMajor || shape.len() != 2 {
+            return Array { shape, data };
+        }
+
+        let (rows, cols) = (shape[0], shape[1]);
+        let mut buffer = Vec::with_capacity(data.len());
+        for row in 0..rows {
+            for col in 0..cols {
+                buffer.push(data[col * rows + row]);
+            }
+        }
+
+        Array {
+            shape,
+            data: buffer,
+        }
+    }
+}
+
+impl<T: Float> Network<T> {
+    // Loads one .npy file per layer for the weights and another for the biases
+    pub fn load_npy<P: AsRef<Path>>(
+        &mut self,
+        weights: &[P],
+        biases: &[P],
+    ) -> Result<(), ImportError> {
+        let layers = self.weights().len();
+        if weights.len() != layers || biases.len() != layers {
+            return Err(ImportError::LayerCount {
+                expected: layers,
+                found: weights.len().min(biases.len()),
+            });
+        }
+
+        load_layers(self, |kind, layer| {
+            let path = if kind == WEIGHTS {
+                &weights[layer]
+            } else {
+                &biases[layer]
+            };
+            parse_npy(&fs::read(path)?)
+        })
+    }
+
+    pub fn load_npz<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ImportError> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+
+        load_layers(self, |kind, layer| {
+            let name = format!("{}_{}", kind, layer);
+            let mut entry = match archive.by_name(&format!("{}.npy", name)) {
+                Ok(entry) => entry,
+                Err(zip::result::ZipError::FileNotFound) => {
+                    return Err(ImportError::MissingArray(name))
+                }
+                Err(error) => return Err(error.into()),
+            };
+            let mut bytes = vec![];
+            entry.read_to_end(&mut bytes)?;
+            parse_npy(&bytes)
+        })
+    }
+
+    // safetensors has no ordering flag, so the layout of the stored matrices must be given
+    pub fn load_safetensors<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        layout: Layout,
+    ) -> Result<(), ImportError> {
+        let bytes = fs::read(path)?;
+        let tensors = SafeTensors::deserialize(&bytes)?;
+
+        load_layers(self, |kind, layer| {
+            let name = format!("{}_{}", kind, layer);
+            let tensor = tensors
+                .tensor(&name)
+                .map_err(|_| ImportError::MissingArray(name))?;
+            let data = decode(tensor.data(), tensor.dtype(), false)?;
+            Ok(Array::new(tensor.shape().to_vec(), data, layout))
+        })
+    }
+}
+
+// Reads and validates every layer before touching the network so a failed import changes nothing
+fn load_layers<T, F>(network: &mut Network<T>, mut read: F) -> Result<(), ImportError>
+where
+    T: Float,
+    F: FnMut(&str, usize) -> Result<Array, ImportError>,
+{
+    let sizes = network.layer_sizes().to_vec();
+    let mut layers = vec![];
+
+    for layer in 0..sizes.len() - 1 {
+        let (inputs, outputs) = (sizes[layer], sizes[layer + 1]);
+
+        let weights = read(WEIGHTS, layer)?;
+        if weights.shape != [outputs, inputs] {
+            return Err(ImportError::ShapeMismatch {
+                name: format!("{}_{}", WEIGHTS, layer),
+                expected: vec![outputs, inputs],
+                found: weights.shape,
+            });
+        }
+
+        let biases = read(BIASES, layer)?;
+        if biases.shape != [outputs] && biases.shape != [outputs, 1] {
+            return Err(ImportError::ShapeMismatch {
+                name: format!("{}_{}", BIASES, layer),
+                expected: vec![outputs],
+                found: biases.shape,
+            });
+        }
+
+        let convert = |data: Vec<f64>| data.into_iter().map(float::cast).collect::<Vec<T>>();
+        layers.push((
+            Matrix::from_vec(&convert(weights.data), outputs, inputs),
+            Matrix::from_vec(&convert(biases.data), outputs, 1),
+        ));
+    }
+
+    for (layer, (weights, biases)) in layers.into_iter().enumerate() {
+        network.set_layer(layer, weights, biases);
+    }
+
+    Ok(())
+}
+
+fn parse_npy(bytes: &[u8]) -> Result<Array, ImportError> {
+    if bytes.len() < 10 || &bytes[..6] != b"\x93NUMPY" {
+        return Err(ImportError::Format("missing npy magic string".to_string()));
+    }
+
+    let (header_len, start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            12,
+        ),
+        version => {
+            return Err(ImportError::Format(format!(
+                "unsupported npy version {}",
+                version
+            )))
+        }
+    };
+    if bytes.len() < start + header_len {
+        return Err(ImportError::Format("truncated header".to_string()));
+    }
+
+    let header = String::from_utf8_lossy(&bytes[start..start + header_len]);
+    let descr = header_value(&header, "descr")?.trim_matches(|c| c == '\'' || c == '"');
+    let fortran_order = header_value(&header, "fortran_order")? == "True";
+    let shape = header_value(&header, "shape")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(|dim| dim.trim())
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|_| ImportError::Format("invalid shape".to_string()))?;
+
+    let (dtype, big_endian) = match descr {
+        "<f4" => (Dtype::F32, false),
+        ">f4" => (Dtype::F32, true),
+        "<f8" => (Dtype::F64, false),
+        ">f8" => (Dtype::F64, true),
+        _ => return Err(ImportError::UnsupportedDtype(descr.to_string())),
+    };
+    let data = decode(&bytes[start + header_len..], dtype, big_endian)?;
+
+    if data.len() != shape.iter().product::<usize>() {
+        return Err(ImportError::Format(
+            "data length does not match shape".to_string(),
+        ));
+    }
+
+    let layout = if fortran_order {
+        Layout::ColumnMajor
+    } else {
+        Layout::RowMajor
+    };
+
+    Ok(Array::new(shape, data, layout))
+}
+
+// Extracts the raw value of a key from the python dict literal in an npy header
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, ImportError> {
+    let missing = || ImportError::Format(format!("header has no '{}'", key));
+    let start = header.find(&format!("'{}':", key)).ok_or_else(missing)? + key.len() + 3;
+    let rest = header[start..].trim_start();
+
+    let end = if rest.starts_with('(') {
+        rest.find(')').map(|end| end + 1)
+    } else {
+        rest.find([',', '}'])
+    };
+
+    Ok(rest[..end.ok_or_else(missing)?].trim())
+}
+
+fn decode(bytes: &[u8], dtype: Dtype, big_endian: bool) -> Result<Vec<f64>, ImportError> {
+    match dtype {
+        Dtype::F32 => Ok(bytes
+            .chunks_exact(4)
+            .map(|chunk| {
+                let chunk = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                if big_endian {
+                    f32::from_be_bytes(chunk) as f64
+                } else {
+                    f32::from_le_bytes(chunk) as f64
+                }
+            })
+            .collect()),
+        Dtype::F64 => Ok(bytes
+            .chunks_exact(8)
+            .map(|chunk| {
+                let mut buffer = [0; 8];
+                buffer.copy_from_slice(chunk);
+                if big_endian {
+                    f64::from_be_bytes(buffer)
+                } else {
+                    f64::from_le_bytes(buffer)
+                }
+            })
+            .collect()),
+        dtype => Err(ImportError::UnsupportedDtype(format!("{:?}", dtype))),
+    }
+}
+
+#[cfg(test)]
+fn npy_bytes(shape: &[usize], data: &[f64], fortran_order: bool) -> Vec<u8> {
+    let shape = shape
+        .iter()
+        .map(|dim| format!("{},", dim))
+        .collect::<String>();
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': {}, 'shape': ({}), }}",
+        if fortran_order { "True" } else { "False" },
+        shape
+    );
+    while (header.len() + 11) % 64 != 0 {
+        header.push(' ');
+    }
+    header.push('\n');
+
+    let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend((header.len() as u16).to_le_bytes());
+    bytes.extend(header.as_bytes());
+    for x in data {
+        bytes.extend(x.to_le_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn load_npy() {
+    use crate::activation::SIGMOID;
+
+    let dir = std::env::temp_dir();
+    let paths = [
+        "neuralnet_load_npy_w0.npy",
+        "neuralnet_load_npy_b0.npy",
+        "neuralnet_load_npy_w1.npy",
+        "neuralnet_load_npy_b1.npy",
+    ]
+    .map(|name| dir.join(name));
+
+    // weights_0 is the 3x2 matrix [[1, 2], [3, 4], [5, 6]] stored column-major
+    fs::write(
+        &paths[0],
+        npy_bytes(&[3, 2], &[1.0, 3.0, 5.0, 2.0, 4.0, 6.0], true),
+    )
+    .unwrap();
+    fs::write(&paths[1], npy_bytes(&[3], &[0.1, 0.2, 0.3], false)).unwrap();
+    fs::write(&paths[2], npy_bytes(&[1, 3], &[7.0, 8.0, 9.0], false)).unwrap();
+    fs::write(&paths[3], npy_bytes(&[1, 1], &[0.4], false)).unwrap();
+
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 0.5);
+    let result = network.load_npy(&[&paths[0], &paths[2]], &[&paths[1], &paths[3]]);
+
+    let mut mismatched = Network::new(vec![3, 3, 1], SIGMOID, 0.5);
+    let before = mismatched.weights().to_vec();
+    let mismatch = mismatched.load_npy(&[&paths[0], &paths[2]], &[&paths[1], &paths[3]]);
+
+    for path in &paths {
+        fs::remove_file(path).unwrap();
+    }
+
+    result.unwrap();
+    assert_eq!(
+        network.weights()[0].data,
+        vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+    );
+    assert_eq!(network.biases()[0].data, vec![0.1, 0.2, 0.3]);
+    assert_eq!(network.weights()[1].data, vec![7.0, 8.0, 9.0]);
+    assert_eq!(network.biases()[1].data, vec![0.4]);
+    assert!(matches!(
+        mismatch,
+        Err(ImportError::ShapeMismatch { expected, found, .. })
+            if expected == vec![3, 3] && found == vec![3, 2]
+    ));
+    assert_eq!(mismatched.weights(), before.as_slice());
+}
+
+#[test]
+fn load_npz() {
+    use crate::activation::SIGMOID;
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+    let path = std::env::temp_dir().join("neuralnet_load_npz.npz");
+    let mut writer = ZipWriter::new(File::create(&path).unwrap());
+    let arrays = [
+        ("weights_0", npy_bytes(&[1, 2], &[1.5, -2.5], false)),
+        ("biases_0", npy_bytes(&[1], &[0.5], false)),
+    ];
+    for (name, bytes) in arrays {
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer.start_file(format!("{}.npy", name), options).unwrap();
+        writer.write_all(&bytes).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let mut network = Network::new(vec![2, 1], SIGMOID, 0.5);
+    let result = network.load_npz(&path);
+    let mut missing = Network::new(vec![2, 1, 1], SIGMOID, 0.5);
+    let missing = missing.load_npz(&path);
+    fs::remove_file(&path).unwrap();
+
+    result.unwrap();
+    assert_eq!(network.weights()[0].data, vec![1.5, -2.5]);
+    assert_eq!(network.biases()[0].data, vec![0.5]);
+    assert!(matches!(missing, Err(ImportError::MissingArray(name)) if name == "weights_1"));
+}
+
+#[test]
+fn load_safetensors() {
+    use crate::activation::SIGMOID;
+    use safetensors::tensor::TensorView;
+
+    let path = std::env::temp_dir().join("neuralnet_load_safetensors.safetensors");
+    // weights_0 is [[1, 2], [3, 4], [5, 6]] in column-major order
+    let weights = [1.0f32, 3.0, 5.0, 2.0, 4.0, 6.0]
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect::<Vec<u8>>();
+    let biases = [0.5f64, 0.25, 0.125]
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect::<Vec<u8>>();
+    let tensors = vec![
+        (
+            "weights_0",
+            TensorView::new(Dtype::F32, vec![3, 2], &weights).unwrap(),
+        ),
+        (
+            "biases_0",
+            TensorView::new(Dtype::F64, vec![3, 1], &biases).unwrap(),
+        ),
+    ];
+    fs::write(&path, safetensors::serialize(tensors, &None).unwrap()).unwrap();
+
+    let mut network = Network::new(vec![2, 3], SIGMOID, 0.5);
+    let result = network.load_safetensors(&path, Layout::ColumnMajor);
+    fs::remove_file(&path).unwrap();
+
+    result.unwrap();
+    assert_eq!(
+        network.weights()[0].data,
+        vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+    );
+    assert_eq!(network.biases()[0].data, vec![0.5, 0.25, 0.125]);
+}
@@ -1,9 +1,30 @@
 mod activation;
+mod float;
+mod import;
 mod matrix;
 mod neat;
 mod network;
+mod onnx;
+mod preprocessing;
+mod quantize;
+mod tensor;
 mod training_data;
 mod utils;
+mod validation;
 
-pub use activation::SIGMOID;
-pub use network::Network;
+pub use activation::{Activation, SIGMOID};
+pub use float::Float;
+pub use import::{ImportError, Layout};
+pub use matrix::{Axis, CscMatrix, CsrMatrix, LinalgError, Lu, Matrix, MatrixView, Qr, Tolerance};
+pub use network::{DivergencePolicy, GradientClip, ModelError, Network, TrainingError};
+pub use onnx::OnnxError;
+pub use preprocessing::{
+    encode_rows, ColumnEncoding, EncodingError, LabelEncoder, OneHotEncoder, OrdinalEncoder,
+    Scaler, Scaling, UnknownCategory,
+};
+pub use quantize::{Granularity, QuantizationReport, QuantizedNetwork};
+pub use tensor::Tensor;
+pub use training_data::{
+    write_predictions, Column, CsvError, CsvHeader, CsvOptions, IdxError, KFold, TrainingData,
+};
+pub use validation::{cross_validate, CrossValidation, Evaluation};
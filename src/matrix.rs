@@ -1,33 +1,41 @@
+mod approx;
+mod axis;
+pub(crate) mod kernels;
+mod linalg;
+mod ops;
+mod random;
+mod sparse;
+mod view;
+
+pub use approx::Tolerance;
+pub use axis::Axis;
+pub use linalg::{LinalgError, Lu, Qr};
+pub use sparse::{CscMatrix, CsrMatrix};
+pub use view::MatrixView;
+
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::float::Float;
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct Matrix {
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Matrix<T = f64> {
     pub rows: usize,
     pub cols: usize,
-    pub data: Vec<f64>,
+    pub data: Vec<T>,
 }
 
-impl Matrix {
-    pub fn random(rows: usize, cols: usize) -> Self {
-        let mut buffer: Vec<f64> = Vec::with_capacity(rows * cols);
-
-        for _ in 0..buffer.capacity() {
-            let num = rand::thread_rng().gen_range(-1.0..1.0);
-            buffer.push(num);
-        }
-
-        Matrix {
-            rows,
-            cols,
-            data: buffer,
-        }
+impl<T: Float> Matrix<T> {
+    // Uniform in [-1, 1), see random.rs for the other distributions
+    pub fn random<R: Rng + ?Sized>(rows: usize, cols: usize, rng: &mut R) -> Self {
+        Matrix::uniform(rows, cols, -T::one(), T::one(), rng)
     }
 
     pub fn zero(rows: usize, cols: usize) -> Self {
-        let mut buffer: Vec<f64> = Vec::with_capacity(rows * cols);
+        let mut buffer: Vec<T> = Vec::with_capacity(rows * cols);
 
         for _ in 0..buffer.capacity() {
-            buffer.push(0.0);
+            buffer.push(T::zero());
         }
 
         Matrix {
@@ -37,7 +45,16 @@ impl Matrix {
         }
     }
 
-    pub fn from_vec(data: &Vec<f64>, rows: usize, cols: usize) -> Self {
+    // n x n identity
+    pub fn eye(n: usize) -> Self {
+        let mut matrix = Matrix::zero(n, n);
+        for i in 0..n {
+            matrix[(i, i)] = T::one();
+        }
+        matrix
+    }
+
+    pub fn from_vec(data: &Vec<T>, rows: usize, cols: usize) -> Self {
         if data.len() != rows * cols {
             println!("{:?}, {}, {}", data, data.capacity(), rows * cols);
             panic!("Vector capacity does not match rows * cols");
@@ -50,118 +67,231 @@ impl Matrix {
         }
     }
 
-    pub fn from_vec_2d(data: Vec<Vec<f64>>) -> Self {
+    pub fn from_vec_2d(data: Vec<Vec<T>>) -> Self {
         Matrix {
             rows: data.len(),
             cols: data[0].len(),
-            data: data.into_iter().flatten().collect::<Vec<f64>>(),
+            data: data.into_iter().flatten().collect::<Vec<T>>(),
         }
     }
 
-    pub fn map(&self, function: &dyn Fn(f64) -> f64) -> Self {
-        Matrix::from_vec(
-            &(self.data)
-                .clone()
-                .into_iter()
-                .map(|x| function(x))
-                .collect(),
-            self.rows,
-            self.cols,
-        )
+    pub fn map(&self, function: &dyn Fn(T) -> T) -> Self {
+        let mut result = self.clone();
+        result.map_inplace(function);
+        result
+    }
+
+    pub fn map_inplace(&mut self, function: &dyn Fn(T) -> T) {
+        self.data.iter_mut().for_each(|x| *x = function(*x));
     }
 
-    pub fn add(&self, other: &Matrix) -> Self {
+    // Same as map, spread over the rayon pool for large matrices
+    #[cfg(feature = "parallel")]
+    pub fn par_map(&self, function: &(dyn Fn(T) -> T + Sync)) -> Self {
+        let mut result = self.clone();
+        result.apply(function);
+        result
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn par_map_inplace(&mut self, function: &(dyn Fn(T) -> T + Sync)) {
+        self.apply(function);
+    }
+
+    // map_inplace for functions that can be shared between threads, parallel when the feature is on
+    pub(crate) fn apply(&mut self, function: impl Fn(T) -> T + Sync) {
+        kernels::apply(&mut self.data, |x| *x = function(*x));
+    }
+
+    pub fn scale_inplace(&mut self, alpha: T) {
+        *self *= alpha;
+    }
+
+    pub fn add_assign(&mut self, other: &Matrix<T>) {
+        *self += other;
+    }
+
+    pub fn subtract_assign(&mut self, other: &Matrix<T>) {
+        *self -= other;
+    }
+
+    pub fn multiply_assign(&mut self, other: &Matrix<T>) {
+        *self *= other;
+    }
+
+    // self += alpha * other, in one pass
+    pub fn axpy(&mut self, alpha: T, other: &Matrix<T>) {
+        self.check_same_shape(other);
+        kernels::zip_apply(&mut self.data, &other.data, |y, x| *y += alpha * x);
+    }
+
+    // Copies other into self, reusing self's buffer when it is large enough
+    pub fn copy_from(&mut self, other: &Matrix<T>) {
+        self.rows = other.rows;
+        self.cols = other.cols;
+        self.data.clear();
+        self.data.extend_from_slice(&other.data);
+    }
+
+    fn check_same_shape(&self, other: &Matrix<T>) {
         if self.rows != other.rows || self.cols != other.cols {
             panic!("Rows or columns do not match between the matrices");
         }
+    }
 
-        let mut buffer: Vec<f64> = Vec::with_capacity(self.rows * self.cols);
+    // Reshapes to rows x cols filled with zeros, only allocating when the buffer has to grow
+    fn reset(&mut self, rows: usize, cols: usize) {
+        self.rows = rows;
+        self.cols = cols;
+        self.data.clear();
+        self.data.resize(rows * cols, T::zero());
+    }
+
+    pub fn add(&self, other: &Matrix<T>) -> Self {
+        self + other
+    }
+
+    pub fn subtract(&self, other: &Matrix<T>) -> Self {
+        self - other
+    }
 
-        for i in 0..self.data.len() {
-            let num = self.data[i] + other.data[i];
-            buffer.push(num);
+    pub fn dot_multiply(&self, other: &Matrix<T>) -> Self {
+        if self.cols != other.rows {
+            panic!("Size mismatch when dot multiplying matrices. Matrix A columns must match Matrix B rows.");
         }
 
+        let mut buffer: Vec<T> = vec![T::zero(); self.rows * other.cols];
+        kernels::gemm(
+            &self.data,
+            &other.data,
+            &mut buffer,
+            self.rows,
+            self.cols,
+            other.cols,
+        );
+
         Matrix {
             rows: self.rows,
-            cols: self.cols,
+            cols: other.cols,
             data: buffer,
         }
     }
 
-    pub fn subtract(&self, other: &Matrix) -> Self {
-        if self.rows != other.rows || self.cols != other.cols {
-            panic!("Rows or columns do not match between the matrices");
+    // Same as self.dot_multiply(&other.transpose()) without materializing the transpose
+    pub fn dot_transposed(&self, other: &Matrix<T>) -> Self {
+        if self.cols != other.cols {
+            panic!("Size mismatch when dot multiplying matrices. Matrix A columns must match Matrix B columns.");
         }
 
-        let mut buffer: Vec<f64> = Vec::with_capacity(self.rows * self.cols);
-
-        for i in 0..self.data.len() {
-            let num = self.data[i] - other.data[i];
-            buffer.push(num);
-        }
+        let mut buffer: Vec<T> = vec![T::zero(); self.rows * other.rows];
+        kernels::gemm_nt(
+            &self.data,
+            &other.data,
+            &mut buffer,
+            self.rows,
+            self.cols,
+            other.rows,
+        );
 
         Matrix {
             rows: self.rows,
-            cols: self.cols,
+            cols: other.rows,
             data: buffer,
         }
     }
 
-    pub fn dot_multiply(&self, other: &Matrix) -> Self {
-        if self.cols != other.rows {
-            panic!("Size mismatch when dot multiplying matrices. Matrix A columns must match Matrix B rows.");
+    // Same as self.transpose().dot_multiply(other) without materializing the transpose
+    pub fn transposed_dot(&self, other: &Matrix<T>) -> Self {
+        if self.rows != other.rows {
+            panic!("Size mismatch when dot multiplying matrices. Matrix A rows must match Matrix B rows.");
         }
 
-        let mut buffer: Vec<f64> = vec![0.0; self.rows * other.cols];
-
-        for a_r in 0..self.rows {
-            for b_c in 0..other.cols {
-                let mut sum: f64 = 0.0;
-
-                for a_c in 0..self.cols {
-                    sum += self.data[a_r * self.cols + a_c] * other.data[a_c * other.cols + b_c];
-                }
-
-                buffer[a_r * other.cols + b_c] = sum;
-            }
-        }
+        let mut buffer: Vec<T> = vec![T::zero(); self.cols * other.cols];
+        kernels::gemm_tn(
+            &self.data,
+            &other.data,
+            &mut buffer,
+            self.cols,
+            self.rows,
+            other.cols,
+        );
 
         Matrix {
-            rows: self.rows,
+            rows: self.cols,
             cols: other.cols,
             data: buffer,
         }
     }
 
-    pub fn multiply(&self, other: &Matrix) -> Self {
-        if self.cols != other.cols || self.rows != other.rows {
-            panic!("Size mismatch when multiplying matrices");
+    // The _into variants write the product into output, which is resized as needed
+    pub fn dot_multiply_into(&self, other: &Matrix<T>, output: &mut Matrix<T>) {
+        if self.cols != other.rows {
+            panic!("Size mismatch when dot multiplying matrices. Matrix A columns must match Matrix B rows.");
         }
 
-        let mut buffer: Vec<f64> = Vec::with_capacity(self.rows * self.cols);
+        output.reset(self.rows, other.cols);
+        kernels::gemm(
+            &self.data,
+            &other.data,
+            &mut output.data,
+            self.rows,
+            self.cols,
+            other.cols,
+        );
+    }
 
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                let idx = row * self.cols + col;
-                buffer.push(self.data[idx] * other.data[idx]);
-            }
+    pub fn dot_transposed_into(&self, other: &Matrix<T>, output: &mut Matrix<T>) {
+        if self.cols != other.cols {
+            panic!("Size mismatch when dot multiplying matrices. Matrix A columns must match Matrix B columns.");
         }
 
-        Matrix {
-            rows: self.rows,
-            cols: other.cols,
-            data: buffer,
+        output.reset(self.rows, other.rows);
+        kernels::gemm_nt(
+            &self.data,
+            &other.data,
+            &mut output.data,
+            self.rows,
+            self.cols,
+            other.rows,
+        );
+    }
+
+    pub fn transposed_dot_into(&self, other: &Matrix<T>, output: &mut Matrix<T>) {
+        if self.rows != other.rows {
+            panic!("Size mismatch when dot multiplying matrices. Matrix A rows must match Matrix B rows.");
         }
+
+        output.reset(self.cols, other.cols);
+        kernels::gemm_tn(
+            &self.data,
+            &other.data,
+            &mut output.data,
+            self.cols,
+            self.rows,
+            other.cols,
+        );
+    }
+
+    pub fn multiply(&self, other: &Matrix<T>) -> Self {
+        self * other
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.data.iter().all(|x| x.is_finite())
+    }
+
+    // Whether adding other would leave every element finite, without writing anything
+    pub(crate) fn sum_is_finite(&self, other: &Matrix<T>) -> bool {
+        self.check_same_shape(other);
+        self.data
+            .iter()
+            .zip(&other.data)
+            .all(|(x, y)| (*x + *y).is_finite())
     }
 
     pub fn transpose(&self) -> Self {
-        let mut buffer = Vec::with_capacity(self.rows * self.cols);
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                buffer.push(self.data[col * self.rows + row]);
-            }
-        }
+        let mut buffer = vec![T::zero(); self.rows * self.cols];
+        kernels::transpose(&self.data, &mut buffer, self.rows, self.cols);
         Matrix {
             rows: self.cols,
             cols: self.rows,
@@ -170,7 +300,7 @@ impl Matrix {
     }
 }
 
-impl std::fmt::Display for Matrix {
+impl<T: Float> std::fmt::Display for Matrix<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let spacer = "--------------------";
         write!(f, "\nrows: {}, cols: {}", self.rows, self.cols);
@@ -189,23 +319,23 @@ impl std::fmt::Display for Matrix {
 #[test]
 #[should_panic]
 fn add() {
-    let matrix = Matrix::random(2, 2);
-    let other = Matrix::random(2, 3);
+    let matrix: Matrix = Matrix::random(2, 2, &mut rand::thread_rng());
+    let other = Matrix::random(2, 3, &mut rand::thread_rng());
     matrix.add(&other);
 }
 
 #[test]
 #[should_panic]
 fn subtract() {
-    let matrix = Matrix::random(3, 2);
-    let other = Matrix::random(2, 2);
+    let matrix: Matrix = Matrix::random(3, 2, &mut rand::thread_rng());
+    let other = Matrix::random(2, 2, &mut rand::thread_rng());
     matrix.subtract(&other);
 }
 
 #[test]
 fn dot_multiply() {
-    let mut matrix = Matrix::random(2, 3);
-    let mut other = Matrix::random(3, 2);
+    let mut matrix = Matrix::random(2, 3, &mut rand::thread_rng());
+    let mut other = Matrix::random(3, 2, &mut rand::thread_rng());
 
     let a: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
     matrix.data = a;
@@ -213,54 +343,53 @@ fn dot_multiply() {
     let b: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
     other.data = b;
 
-    let expected_result = vec![22.0, 28.0, 49.0, 64.0];
+    let expected_result = Matrix::from_vec(&vec![22.0, 28.0, 49.0, 64.0], 2, 2);
 
     let result = matrix.dot_multiply(&other);
 
-    assert_eq!(result.rows, matrix.rows);
-    assert_eq!(result.cols, other.cols);
-    assert_eq!(result.data, expected_result);
+    crate::assert_matrix_shape!(result, matrix.rows, other.cols);
+    crate::assert_matrix_close!(result, expected_result);
 }
 
 #[test]
 fn feed_forward() {
-    let mut input = Matrix::random(1, 2);
+    let mut input = Matrix::random(1, 2, &mut rand::thread_rng());
     input.data = vec![1.0, 3.0];
 
-    let mut hidden_1 = Matrix::random(2, 2);
+    let mut hidden_1 = Matrix::random(2, 2, &mut rand::thread_rng());
     hidden_1.data = vec![1.0, 1.0, 1.0, 1.0];
 
-    let mut hidden_2 = Matrix::random(2, 2);
+    let mut hidden_2 = Matrix::random(2, 2, &mut rand::thread_rng());
     hidden_2.data = vec![2.0, 2.0, 2.0, 2.0];
 
-    let mut output = Matrix::random(2, 1);
+    let mut output = Matrix::random(2, 1, &mut rand::thread_rng());
     output.data = vec![3.0, 3.0];
     let bias = 1.0;
 
     hidden_1 = input.dot_multiply(&hidden_1);
-    assert_eq!(hidden_1.data, [4.0, 4.0]);
+    crate::assert_matrix_close!(hidden_1, Matrix::from_vec(&vec![4.0, 4.0], 1, 2));
     hidden_1.data = hidden_1.data.iter().map(|x| x + bias).collect::<Vec<f64>>();
-    assert_eq!(hidden_1.data, [5.0, 5.0]);
+    crate::assert_matrix_close!(hidden_1, Matrix::from_vec(&vec![5.0, 5.0], 1, 2));
 
     hidden_2 = hidden_1.dot_multiply(&hidden_2);
-    assert_eq!(hidden_2.data, [20.0, 20.0]);
+    crate::assert_matrix_close!(hidden_2, Matrix::from_vec(&vec![20.0, 20.0], 1, 2));
     hidden_2.data = hidden_2.data.iter().map(|x| x + bias).collect::<Vec<f64>>();
-    assert_eq!(hidden_2.data, [21.0, 21.0]);
+    crate::assert_matrix_close!(hidden_2, Matrix::from_vec(&vec![21.0, 21.0], 1, 2));
 
     output = hidden_2.dot_multiply(&output);
-    assert_eq!(output.data, [126.0]);
+    crate::assert_matrix_close!(output, Matrix::from_vec(&vec![126.0], 1, 1));
     output.data = output.data.iter().map(|x| x + bias).collect::<Vec<f64>>();
-    assert_eq!(output.data, [127.0]);
+    crate::assert_matrix_close!(output, Matrix::from_vec(&vec![127.0], 1, 1));
 }
 
 #[test]
 fn transpose() {
-    let mut a = Matrix::random(1, 2);
+    let mut a = Matrix::random(1, 2, &mut rand::thread_rng());
     a.data = vec![1.0, 2.0];
 
     println!("{}", &a);
 
-    let mut expected = Matrix::random(2, 1);
+    let mut expected = Matrix::random(2, 1, &mut rand::thread_rng());
     expected.data = vec![1.0, 2.0];
 
     let transposed = a.transpose();
@@ -269,3 +398,165 @@ fn transpose() {
 
     assert_eq!(transposed, expected);
 }
+
+#[test]
+fn random_seeded() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let a: Matrix = Matrix::random(3, 4, &mut StdRng::seed_from_u64(7));
+    let b: Matrix = Matrix::random(3, 4, &mut StdRng::seed_from_u64(7));
+    let c: Matrix = Matrix::random(3, 4, &mut StdRng::seed_from_u64(8));
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+}
+
+#[test]
+fn single_precision() {
+    let a = Matrix::from_vec(&vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3);
+    let b = Matrix::from_vec(&vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], 3, 2);
+
+    assert_eq!(a.dot_multiply(&b).data, vec![22.0f32, 28.0, 49.0, 64.0]);
+    assert_eq!(a.add(&a).data, vec![2.0f32, 4.0, 6.0, 8.0, 10.0, 12.0]);
+}
+
+#[test]
+fn transpose_rectangular() {
+    let a = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3);
+
+    let transposed = a.transpose();
+
+    assert_eq!((transposed.rows, transposed.cols), (3, 2));
+    assert_eq!(transposed.data, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    assert_eq!(transposed.transpose(), a);
+}
+
+#[test]
+fn blocked_dot_multiply() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(9);
+    let a: Matrix = Matrix::random(70, 130, &mut rng);
+    let b: Matrix = Matrix::random(130, 67, &mut rng);
+
+    let result = a.dot_multiply(&b);
+
+    for row in 0..a.rows {
+        for col in 0..b.cols {
+            let mut expected = 0.0;
+            for i in 0..a.cols {
+                expected += a.get(row, i) * b.get(i, col);
+            }
+            assert!((result.get(row, col) - expected).abs() < 1e-10);
+        }
+    }
+}
+
+#[test]
+fn transposed_products() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(10);
+    let a: Matrix = Matrix::random(70, 130, &mut rng);
+    let b: Matrix = Matrix::random(67, 130, &mut rng);
+    let c: Matrix = Matrix::random(70, 3, &mut rng);
+
+    let nt = a.dot_transposed(&b);
+    let expected_nt = a.dot_multiply(&b.transpose());
+    let tn = a.transposed_dot(&c);
+    let expected_tn = a.transpose().dot_multiply(&c);
+
+    assert_eq!((nt.rows, nt.cols), (70, 67));
+    assert_eq!((tn.rows, tn.cols), (130, 3));
+    for (x, y) in nt.data.iter().zip(&expected_nt.data) {
+        assert!((x - y).abs() < 1e-10);
+    }
+    for (x, y) in tn.data.iter().zip(&expected_tn.data) {
+        assert!((x - y).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn in_place_ops() {
+    let mut matrix = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0], 2, 2);
+    let other = Matrix::from_vec(&vec![0.5, -1.0, 2.0, 0.0], 2, 2);
+
+    let mut expected = matrix.add(&other);
+    matrix.add_assign(&other);
+    assert_eq!(matrix, expected);
+
+    expected = matrix.subtract(&other);
+    matrix.subtract_assign(&other);
+    assert_eq!(matrix, expected);
+
+    expected = matrix.multiply(&other);
+    matrix.multiply_assign(&other);
+    assert_eq!(matrix, expected);
+
+    expected = matrix.map(&|x| x * 3.0 - 1.0);
+    matrix.map_inplace(&|x| x * 3.0 - 1.0);
+    assert_eq!(matrix, expected);
+
+    matrix.scale_inplace(2.0);
+    assert_eq!(matrix.data, vec![1.0, -14.0, 34.0, -2.0]);
+
+    matrix.axpy(-2.0, &other);
+    assert_eq!(matrix.data, vec![0.0, -12.0, 30.0, -2.0]);
+}
+
+#[test]
+fn map_stateful_closure() {
+    let matrix = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0], 2, 2);
+
+    // map takes closures that are not Sync, whatever the features
+    let calls = std::cell::Cell::new(0);
+    let doubled = matrix.map(&|x| {
+        calls.set(calls.get() + 1);
+        x * 2.0
+    });
+    assert_eq!(calls.get(), 4);
+    assert_eq!(doubled.data, vec![2.0, 4.0, 6.0, 8.0]);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn par_map() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut matrix: Matrix = Matrix::random(300, 300, &mut StdRng::seed_from_u64(12));
+    let expected = matrix.map(&|x| x.tanh() - 1.0);
+
+    assert_eq!(matrix.par_map(&|x| x.tanh() - 1.0), expected);
+    matrix.par_map_inplace(&|x| x.tanh() - 1.0);
+    assert_eq!(matrix, expected);
+}
+
+#[test]
+#[should_panic]
+fn axpy_shape_mismatch() {
+    let mut matrix: Matrix = Matrix::zero(2, 2);
+    matrix.axpy(1.0, &Matrix::zero(2, 3));
+}
+
+#[test]
+fn products_into() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(11);
+    let a: Matrix = Matrix::random(5, 3, &mut rng);
+    let b = Matrix::random(3, 4, &mut rng);
+    let c = Matrix::random(4, 3, &mut rng);
+
+    // Start from a buffer of the wrong shape with stale values to check it gets reset
+    let mut output = Matrix::from_vec(&vec![9.0; 40], 8, 5);
+    let buffer = output.data.as_ptr();
+
+    a.dot_multiply_into(&b, &mut output);
+    assert_eq!(output, a.dot_multiply(&b));
+    a.dot_transposed_into(&c, &mut output);
+    assert_eq!(output, a.dot_transposed(&c));
+    b.transposed_dot_into(&c.transpose(), &mut output);
+    assert_eq!(output, b.transposed_dot(&c.transpose()));
+
+    assert_eq!(output.data.as_ptr(), buffer);
+}
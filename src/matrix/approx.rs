@@ -0,0 +1,245 @@
+use super::Matrix;
+use crate::float::{self, Float};
+
+// Cells a and b are close when any one of the criteria holds:
+// |a - b| <= abs, |a - b| <= rel * max(|a|, |b|), or they are at most ulps representable values apart
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    pub abs: f64,
+    pub rel: f64,
+    pub ulps: u64,
+}
+
+impl Tolerance {
+    // Only identical values (and 0.0 == -0.0) are close, start from this to enable single criteria
+    pub const EXACT: Tolerance = Tolerance {
+        abs: 0.0,
+        rel: 0.0,
+        ulps: 0,
+    };
+
+    fn is_close<T: Float>(&self, a: T, b: T) -> bool {
+        let diff = (a - b).abs();
+        a == b
+            || diff <= float::cast(self.abs)
+            || diff <= float::cast::<T>(self.rel) * a.abs().max(b.abs())
+            || a.ulps(b) <= self.ulps
+    }
+}
+
+// Tight enough to catch real errors while absorbing reordered f64 sums; f32 results rely on ulps
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance {
+            abs: 1e-12,
+            rel: 1e-9,
+            ulps: 4,
+        }
+    }
+}
+
+// Cells listed in an approx_diff report before the rest are summarized
+const MAX_REPORTED: usize = 10;
+
+impl<T: Float> Matrix<T> {
+    pub fn approx_eq(&self, other: &Matrix<T>, tolerance: Tolerance) -> bool {
+        self.rows == other.rows
+            && self.cols == other.cols
+            && self
+                .data
+                .iter()
+                .zip(&other.data)
+                .all(|(a, b)| tolerance.is_close(*a, *b))
+    }
+
+    // None when the matrices are close, otherwise a description of the shape or of the differing cells
+    pub fn approx_diff(&self, other: &Matrix<T>, tolerance: Tolerance) -> Option<String> {
+        if self.rows != other.rows || self.cols != other.cols {
+            return Some(format!(
+                "shapes differ: {}x{} vs {}x{}",
+                self.rows, self.cols, other.rows, other.cols
+            ));
+        }
+
+        let mismatches: Vec<usize> = (0..self.data.len())
+            .filter(|i| !tolerance.is_close(self.data[*i], other.data[*i]))
+            .collect();
+        if mismatches.is_empty() {
+            return None;
+        }
+
+        let mut report = format!(
+            "{} of {} cells differ ({:?})",
+            mismatches.len(),
+            self.data.len(),
+            tolerance
+        );
+        for i in mismatches.iter().take(MAX_REPORTED) {
+            let (a, b) = (self.data[*i], other.data[*i]);
+            report.push_str(&format!(
+                "\n  ({}, {}): {} vs {}, diff {:e}, {} ulps",
+                i / self.cols,
+                i % self.cols,
+                a,
+                b,
+                (a - b).abs().to_f64().unwrap(),
+                a.ulps(b)
+            ));
+        }
+        if mismatches.len() > MAX_REPORTED {
+            report.push_str(&format!(
+                "\n  ... and {} more",
+                mismatches.len() - MAX_REPORTED
+            ));
+        }
+        Some(report)
+    }
+}
+
+// assert_matrix_close!(left, right) uses Tolerance::default(). Individual criteria can be given
+// instead, any not mentioned stay exact: assert_matrix_close!(left, right, abs = 1e-6, ulps = 8)
+#[macro_export]
+macro_rules! assert_matrix_close {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_matrix_close!(@check $left, $right, $crate::Tolerance::default())
+    };
+    ($left:expr, $right:expr, $($criterion:ident = $value:expr),+ $(,)?) => {
+        $crate::assert_matrix_close!(
+            @check $left,
+            $right,
+            $crate::Tolerance { $($criterion: $value,)+ ..$crate::Tolerance::EXACT }
+        )
+    };
+    (@check $left:expr, $right:expr, $tolerance:expr) => {
+        if let Some(report) = $crate::Matrix::approx_diff(&$left, &$right, $tolerance) {
+            panic!(
+                "assertion failed: `{}` is not close to `{}`\n{}",
+                stringify!($left),
+                stringify!($right),
+                report
+            );
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! assert_matrix_shape {
+    ($matrix:expr, $rows:expr, $cols:expr $(,)?) => {{
+        let matrix = &$matrix;
+        if (matrix.rows, matrix.cols) != ($rows, $cols) {
+            panic!(
+                "assertion failed: `{}` is {}x{}, expected {}x{}",
+                stringify!($matrix),
+                matrix.rows,
+                matrix.cols,
+                $rows,
+                $cols
+            );
+        }
+    }};
+}
+
+#[test]
+fn approx_eq() {
+    let a = Matrix::from_vec(&vec![1.0, 100.0, 0.0, -2.0], 2, 2);
+    let b = Matrix::from_vec(&vec![1.0 + 1e-13, 100.0 + 1e-8, 1e-13, -2.0], 2, 2);
+
+    assert!(a.approx_eq(&b, Tolerance::default()));
+    assert!(!a.approx_eq(&b, Tolerance::EXACT));
+    assert!(!a.approx_eq(
+        &b,
+        Tolerance {
+            abs: 1e-12,
+            ..Tolerance::EXACT
+        }
+    ));
+    assert!(a.approx_eq(
+        &b,
+        Tolerance {
+            abs: 1e-7,
+            ..Tolerance::EXACT
+        }
+    ));
+    assert!(!a.approx_eq(
+        &b,
+        Tolerance {
+            rel: 1e-9,
+            ..Tolerance::EXACT
+        }
+    ));
+    assert!(!a.approx_eq(
+        &Matrix::zero(4, 1),
+        Tolerance {
+            abs: 1e9,
+            ..Tolerance::EXACT
+        }
+    ));
+
+    let next = Matrix::from_vec(&vec![f32::from_bits(1.0f32.to_bits() + 2)], 1, 1);
+    let one = Matrix::from_vec(&vec![1.0f32], 1, 1);
+    assert!(one.approx_eq(
+        &next,
+        Tolerance {
+            ulps: 2,
+            ..Tolerance::EXACT
+        }
+    ));
+    assert!(!one.approx_eq(
+        &next,
+        Tolerance {
+            ulps: 1,
+            ..Tolerance::EXACT
+        }
+    ));
+}
+
+#[test]
+fn approx_diff_report() {
+    let a: Matrix = Matrix::zero(3, 5);
+    let mut b = Matrix::zero(3, 5);
+    b.data.iter_mut().for_each(|x| *x = 0.5);
+    b[(0, 0)] = 0.0;
+
+    let report = a.approx_diff(&b, Tolerance::default()).unwrap();
+    assert!(report.starts_with("14 of 15 cells differ"));
+    assert!(report.contains("\n  (0, 1): 0 vs 0.5, diff 5e-1"));
+    assert!(report.ends_with("... and 4 more"));
+
+    assert_eq!(
+        a.approx_diff(&Matrix::zero(5, 3), Tolerance::default()),
+        Some("shapes differ: 3x5 vs 5x3".to_string())
+    );
+    assert_eq!(a.approx_diff(&a, Tolerance::EXACT), None);
+}
+
+#[test]
+fn assertion_macros() {
+    let a = Matrix::from_vec(&vec![1.0, 2.0], 1, 2);
+
+    crate::assert_matrix_close!(a, a.map(&|x| x + 1e-13));
+    crate::assert_matrix_close!(a, a.map(&|x| x + 1e-7), abs = 1e-6);
+    crate::assert_matrix_close!(a, a.map(&|x| x * (1.0 + 1e-7)), rel = 1e-6, ulps = 2);
+    crate::assert_matrix_shape!(a, 1, 2);
+
+    // Both expand to a single expression, so they also work as match arms
+    for matrix in [a.clone(), a.transpose()] {
+        match matrix.rows {
+            1 => crate::assert_matrix_shape!(matrix, 1, 2),
+            _ => crate::assert_matrix_close!(matrix, a.transpose()),
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "(0, 1): 2 vs 2.1")]
+fn assert_matrix_close_failure() {
+    let a = Matrix::from_vec(&vec![1.0, 2.0], 1, 2);
+    crate::assert_matrix_close!(a, Matrix::from_vec(&vec![1.0, 2.1], 1, 2));
+}
+
+#[test]
+#[should_panic(expected = "is 1x2, expected 2x1")]
+fn assert_matrix_shape_failure() {
+    let a: Matrix = Matrix::zero(1, 2);
+    crate::assert_matrix_shape!(a, 2, 1);
+}
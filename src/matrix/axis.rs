@@ -0,0 +1,225 @@
+use super::Matrix;
+use crate::float::{self, Float};
+
+// Rows reduces every column down to a single value (1 x cols), Columns reduces every row (rows x 1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis {
+    Rows,
+    Columns,
+}
+
+impl<T: Float> Matrix<T> {
+    // other may be the same shape, a rows x 1 column, a 1 x cols row or a 1 x 1 scalar
+    pub fn broadcast_add(&self, other: &Matrix<T>) -> Self {
+        self.broadcast(other, &|x, y| x + y)
+    }
+
+    pub fn broadcast_subtract(&self, other: &Matrix<T>) -> Self {
+        self.broadcast(other, &|x, y| x - y)
+    }
+
+    pub fn broadcast_multiply(&self, other: &Matrix<T>) -> Self {
+        self.broadcast(other, &|x, y| x * y)
+    }
+
+    pub fn broadcast_divide(&self, other: &Matrix<T>) -> Self {
+        self.broadcast(other, &|x, y| x / y)
+    }
+
+    fn broadcast(&self, other: &Matrix<T>, op: &dyn Fn(T, T) -> T) -> Self {
+        if (other.rows != self.rows && other.rows != 1)
+            || (other.cols != self.cols && other.cols != 1)
+        {
+            panic!(
+                "Cannot broadcast a {}x{} matrix onto a {}x{} matrix",
+                other.rows, other.cols, self.rows, self.cols
+            );
+        }
+
+        let mut buffer: Vec<T> = Vec::with_capacity(self.rows * self.cols);
+        for row in 0..self.rows {
+            let other_row = if other.rows == 1 { 0 } else { row };
+            for col in 0..self.cols {
+                let other_col = if other.cols == 1 { 0 } else { col };
+                buffer.push(op(
+                    self.data[row * self.cols + col],
+                    other.data[other_row * other.cols + other_col],
+                ));
+            }
+        }
+
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: buffer,
+        }
+    }
+
+    pub fn sum(&self, axis: Axis) -> Self {
+        self.fold(axis, T::zero(), &|sum, x| sum + x)
+    }
+
+    pub fn mean(&self, axis: Axis) -> Self {
+        let count: T = float::cast(self.axis_len(axis) as f64);
+        self.sum(axis).map(&|x| x / count)
+    }
+
+    pub fn max(&self, axis: Axis) -> Self {
+        self.fold(axis, T::neg_infinity(), &|max, x| max.max(x))
+    }
+
+    pub fn min(&self, axis: Axis) -> Self {
+        self.fold(axis, T::infinity(), &|min, x| min.min(x))
+    }
+
+    // Population variance, matching what normalization layers use
+    pub fn variance(&self, axis: Axis) -> Self {
+        let mean = self.mean(axis);
+        let count: T = float::cast(self.axis_len(axis) as f64);
+        self.broadcast_subtract(&mean)
+            .fold(axis, T::zero(), &|sum, x| sum + x * x)
+            .map(&|x| x / count)
+    }
+
+    // L2 norm of every row or column
+    pub fn norm(&self, axis: Axis) -> Self {
+        self.fold(axis, T::zero(), &|sum, x| sum + x * x)
+            .map(&|x| x.sqrt())
+    }
+
+    // Index of the largest value in every column (Rows) or every row (Columns), first one on ties
+    pub fn argmax(&self, axis: Axis) -> Vec<usize> {
+        self.check_axis(axis);
+        let (outer, inner) = self.reduced_shape(axis);
+        let mut best = vec![0; outer];
+
+        for (i, best) in best.iter_mut().enumerate() {
+            for j in 1..inner {
+                if self.data[self.axis_index(axis, i, j)]
+                    > self.data[self.axis_index(axis, i, *best)]
+                {
+                    *best = j;
+                }
+            }
+        }
+
+        best
+    }
+
+    fn fold(&self, axis: Axis, init: T, function: &dyn Fn(T, T) -> T) -> Self {
+        self.check_axis(axis);
+        let (outer, inner) = self.reduced_shape(axis);
+        let data = (0..outer)
+            .map(|i| {
+                (0..inner).fold(init, |acc, j| {
+                    function(acc, self.data[self.axis_index(axis, i, j)])
+                })
+            })
+            .collect();
+
+        match axis {
+            Axis::Rows => Matrix::from_vec(&data, 1, self.cols),
+            Axis::Columns => Matrix::from_vec(&data, self.rows, 1),
+        }
+    }
+
+    fn check_axis(&self, axis: Axis) {
+        if self.axis_len(axis) == 0 {
+            panic!("Cannot reduce along an empty axis");
+        }
+    }
+
+    // Number of values that get reduced into each result
+    fn axis_len(&self, axis: Axis) -> usize {
+        match axis {
+            Axis::Rows => self.rows,
+            Axis::Columns => self.cols,
+        }
+    }
+
+    // (number of results, values per result)
+    fn reduced_shape(&self, axis: Axis) -> (usize, usize) {
+        match axis {
+            Axis::Rows => (self.cols, self.rows),
+            Axis::Columns => (self.rows, self.cols),
+        }
+    }
+
+    // Position in data of the j-th value reduced into the i-th result
+    fn axis_index(&self, axis: Axis, i: usize, j: usize) -> usize {
+        match axis {
+            Axis::Rows => j * self.cols + i,
+            Axis::Columns => i * self.cols + j,
+        }
+    }
+}
+
+#[test]
+fn broadcast() {
+    let batch = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3);
+    let column = Matrix::from_vec(&vec![10.0, 20.0], 2, 1);
+    let row = Matrix::from_vec(&vec![1.0, 2.0, 3.0], 1, 3);
+
+    assert_eq!(
+        batch.broadcast_add(&column).data,
+        vec![11.0, 12.0, 13.0, 24.0, 25.0, 26.0]
+    );
+    assert_eq!(
+        batch.broadcast_subtract(&row).data,
+        vec![0.0, 0.0, 0.0, 3.0, 3.0, 3.0]
+    );
+    assert_eq!(
+        batch.broadcast_divide(&row).data,
+        vec![1.0, 1.0, 1.0, 4.0, 2.5, 2.0]
+    );
+    assert_eq!(
+        batch
+            .broadcast_multiply(&Matrix::from_vec(&vec![2.0], 1, 1))
+            .data,
+        vec![2.0, 4.0, 6.0, 8.0, 10.0, 12.0]
+    );
+    assert_eq!(batch.broadcast_add(&batch), batch.add(&batch));
+}
+
+#[test]
+#[should_panic]
+fn broadcast_mismatch() {
+    let batch: Matrix = Matrix::zero(2, 3);
+    batch.broadcast_add(&Matrix::zero(3, 1));
+}
+
+#[test]
+fn reductions() {
+    let matrix = Matrix::from_vec(&vec![1.0, 5.0, 3.0, 4.0, 2.0, 3.0], 2, 3);
+
+    assert_eq!(
+        matrix.sum(Axis::Rows),
+        Matrix::from_vec(&vec![5.0, 7.0, 6.0], 1, 3)
+    );
+    assert_eq!(
+        matrix.sum(Axis::Columns),
+        Matrix::from_vec(&vec![9.0, 9.0], 2, 1)
+    );
+    assert_eq!(matrix.mean(Axis::Rows).data, vec![2.5, 3.5, 3.0]);
+    assert_eq!(matrix.mean(Axis::Columns).data, vec![3.0, 3.0]);
+    assert_eq!(matrix.max(Axis::Rows).data, vec![4.0, 5.0, 3.0]);
+    assert_eq!(matrix.min(Axis::Columns).data, vec![1.0, 2.0]);
+    assert_eq!(matrix.argmax(Axis::Rows), vec![1, 0, 0]);
+    assert_eq!(matrix.argmax(Axis::Columns), vec![1, 0]);
+    assert_eq!(matrix.variance(Axis::Rows).data, vec![2.25, 2.25, 0.0]);
+    assert_eq!(
+        matrix.variance(Axis::Columns).data,
+        vec![8.0 / 3.0, 2.0 / 3.0]
+    );
+    assert_eq!(
+        matrix.norm(Axis::Columns).data,
+        vec![35.0f64.sqrt(), 29.0f64.sqrt()]
+    );
+}
+
+#[test]
+#[should_panic]
+fn argmax_empty_axis() {
+    let matrix: Matrix = Matrix::zero(3, 0);
+    matrix.argmax(Axis::Columns);
+}
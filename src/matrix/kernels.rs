@@ -0,0 +1,288 @@
+// Slice level kernels behind the Matrix products. Every matrix is row-major and every product
+// accumulates into `c`, so callers start from a zeroed buffer.
+
+use std::ops::Range;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::float::Float;
+
+// Edge of the square tiles the loops are blocked into, sized so a tile of each operand fits in L1
+const BLOCK: usize = 64;
+
+// Independent accumulators in `dot`, which lets the compiler vectorize the reduction
+const LANES: usize = 8;
+
+// Work (multiply-adds for products, elements otherwise) below which a kernel stays on the calling
+// thread, since handing it to the pool would cost more than it saves
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 1 << 16;
+
+// c (m x n) += a (m x k) * b (k x n)
+pub fn gemm<T: Float>(a: &[T], b: &[T], c: &mut [T], m: usize, k: usize, n: usize) {
+    row_bands(c, m, n, m * k * n, |rows, c| {
+        let a = &a[rows.start * k..rows.end * k];
+        #[cfg(feature = "matrixmultiply")]
+        T::gemm((rows.len(), k, n), a, (k, 1), b, (n, 1), c, (n, 1));
+        #[cfg(not(feature = "matrixmultiply"))]
+        blocked_gemm(a, b, c, rows.len(), k, n);
+    });
+}
+
+// c (m x n) += a (m x k) * b^T, with b stored as (n x k)
+pub fn gemm_nt<T: Float>(a: &[T], b: &[T], c: &mut [T], m: usize, k: usize, n: usize) {
+    row_bands(c, m, n, m * k * n, |rows, c| {
+        let a = &a[rows.start * k..rows.end * k];
+        #[cfg(feature = "matrixmultiply")]
+        T::gemm((rows.len(), k, n), a, (k, 1), b, (1, k), c, (n, 1));
+        #[cfg(not(feature = "matrixmultiply"))]
+        blocked_gemm_nt(a, b, c, rows.len(), k, n);
+    });
+}
+
+// c (m x n) += a^T * b (k x n), with a stored as (k x m)
+pub fn gemm_tn<T: Float>(a: &[T], b: &[T], c: &mut [T], m: usize, k: usize, n: usize) {
+    row_bands(c, m, n, m * k * n, |rows, c| {
+        // Rows of c are columns of a, so a band starts rows.start elements in and keeps stride m
+        let a = &a[rows.start..];
+        #[cfg(feature = "matrixmultiply")]
+        T::gemm((rows.len(), k, n), a, (1, m), b, (n, 1), c, (n, 1));
+        #[cfg(not(feature = "matrixmultiply"))]
+        blocked_gemm_tn(a, m, b, c, rows.len(), k, n);
+    });
+}
+
+// dst (cols x rows) = src (rows x cols)^T
+pub fn transpose<T: Float>(src: &[T], dst: &mut [T], rows: usize, cols: usize) {
+    row_bands(dst, cols, rows, rows * cols, |columns, dst| {
+        blocked_transpose(src, dst, rows, cols, columns)
+    });
+}
+
+// f(x) for every element of x
+pub fn apply<T: Float>(x: &mut [T], f: impl Fn(&mut T) + Sync) {
+    row_bands(x, x.len(), 1, x.len(), |_, x| x.iter_mut().for_each(&f));
+}
+
+// f(x, y) for every pair of elements of x and y, which have the same length
+pub fn zip_apply<T: Float>(x: &mut [T], y: &[T], f: impl Fn(&mut T, T) + Sync) {
+    row_bands(x, x.len(), 1, x.len(), |range, x| {
+        for (x, y) in x.iter_mut().zip(&y[range]) {
+            f(x, *y);
+        }
+    });
+}
+
+// Runs f on bands of whole rows of c (rows x cols), passing the rows each band covers. With the
+// parallel feature the bands are spread over the rayon pool once the work reaches the threshold.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+fn row_bands<T, F>(c: &mut [T], rows: usize, cols: usize, work: usize, f: F)
+where
+    T: Float,
+    F: Fn(Range<usize>, &mut [T]) + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if work >= PARALLEL_THRESHOLD && rows > 1 && cols > 0 {
+        let band = rows.div_ceil(rayon::current_num_threads());
+        c.par_chunks_mut(band * cols)
+            .enumerate()
+            .for_each(|(i, c)| f(i * band..i * band + c.len() / cols, c));
+        return;
+    }
+
+    f(0..rows, c);
+}
+
+// The built-in kernels stay compiled with the matrixmultiply feature so the tests can compare both
+#[cfg_attr(feature = "matrixmultiply", allow(dead_code))]
+fn blocked_gemm<T: Float>(a: &[T], b: &[T], c: &mut [T], m: usize, k: usize, n: usize) {
+    for kk in (0..k).step_by(BLOCK) {
+        let k_end = (kk + BLOCK).min(k);
+        for jj in (0..n).step_by(BLOCK) {
+            let j_end = (jj + BLOCK).min(n);
+            for i in 0..m {
+                let c_row = &mut c[i * n + jj..i * n + j_end];
+                for p in kk..k_end {
+                    axpy(a[i * k + p], &b[p * n + jj..p * n + j_end], c_row);
+                }
+            }
+        }
+    }
+}
+
+#[cfg_attr(feature = "matrixmultiply", allow(dead_code))]
+fn blocked_gemm_nt<T: Float>(a: &[T], b: &[T], c: &mut [T], m: usize, k: usize, n: usize) {
+    for jj in (0..n).step_by(BLOCK) {
+        let j_end = (jj + BLOCK).min(n);
+        for i in 0..m {
+            let a_row = &a[i * k..(i + 1) * k];
+            for j in jj..j_end {
+                c[i * n + j] += dot(a_row, &b[j * k..(j + 1) * k]);
+            }
+        }
+    }
+}
+
+// a is read with a row stride of lda, so a band of columns can be passed without copying it
+#[cfg_attr(feature = "matrixmultiply", allow(dead_code))]
+fn blocked_gemm_tn<T: Float>(
+    a: &[T],
+    lda: usize,
+    b: &[T],
+    c: &mut [T],
+    m: usize,
+    k: usize,
+    n: usize,
+) {
+    for ii in (0..m).step_by(BLOCK) {
+        let i_end = (ii + BLOCK).min(m);
+        for p in 0..k {
+            let b_row = &b[p * n..(p + 1) * n];
+            for i in ii..i_end {
+                axpy(a[p * lda + i], b_row, &mut c[i * n..(i + 1) * n]);
+            }
+        }
+    }
+}
+
+// Writes the rows of dst (cols x rows) that hold the given columns of src (rows x cols), with
+// dst starting at the first of them
+fn blocked_transpose<T: Float>(
+    src: &[T],
+    dst: &mut [T],
+    rows: usize,
+    cols: usize,
+    columns: Range<usize>,
+) {
+    for rr in (0..rows).step_by(BLOCK) {
+        let r_end = (rr + BLOCK).min(rows);
+        for cc in columns.clone().step_by(BLOCK) {
+            let c_end = (cc + BLOCK).min(columns.end);
+            for row in rr..r_end {
+                for col in cc..c_end {
+                    dst[(col - columns.start) * rows + row] = src[row * cols + col];
+                }
+            }
+        }
+    }
+}
+
+// y += alpha * x
+pub fn axpy<T: Float>(alpha: T, x: &[T], y: &mut [T]) {
+    for (y, x) in y.iter_mut().zip(x) {
+        *y += alpha * *x;
+    }
+}
+
+pub fn dot<T: Float>(a: &[T], b: &[T]) -> T {
+    let mut lanes = [T::zero(); LANES];
+    let a_chunks = a.chunks_exact(LANES);
+    let b_chunks = b.chunks_exact(LANES);
+    let mut sum = T::zero();
+
+    for (x, y) in a_chunks.remainder().iter().zip(b_chunks.remainder()) {
+        sum += *x * *y;
+    }
+    for (a, b) in a_chunks.zip(b_chunks) {
+        for lane in 0..LANES {
+            lanes[lane] += a[lane] * b[lane];
+        }
+    }
+
+    lanes.iter().fold(sum, |sum, lane| sum + *lane)
+}
+
+#[test]
+fn dot_lanes() {
+    let a = (0..19).map(|x| x as f64).collect::<Vec<f64>>();
+    let b = vec![2.0; 19];
+
+    assert_eq!(dot(&a, &b), 342.0);
+}
+
+#[cfg(feature = "matrixmultiply")]
+#[test]
+fn backend_matches_blocked() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(12);
+    let (m, k, n) = (37, 70, 19);
+    let a = (0..m * k)
+        .map(|_| rng.gen_range(-1.0..1.0))
+        .collect::<Vec<f64>>();
+    let b = (0..k * n)
+        .map(|_| rng.gen_range(-1.0..1.0))
+        .collect::<Vec<f64>>();
+    let b_t = (0..n * k)
+        .map(|_| rng.gen_range(-1.0..1.0))
+        .collect::<Vec<f64>>();
+    let a_t = (0..k * m)
+        .map(|_| rng.gen_range(-1.0..1.0))
+        .collect::<Vec<f64>>();
+
+    let close = |x: &[f64], y: &[f64]| x.iter().zip(y).all(|(x, y)| (x - y).abs() < 1e-12);
+    let (mut backend, mut blocked) = (vec![1.0; m * n], vec![1.0; m * n]);
+
+    gemm(&a, &b, &mut backend, m, k, n);
+    blocked_gemm(&a, &b, &mut blocked, m, k, n);
+    assert!(close(&backend, &blocked));
+
+    gemm_nt(&a, &b_t, &mut backend, m, k, n);
+    blocked_gemm_nt(&a, &b_t, &mut blocked, m, k, n);
+    assert!(close(&backend, &blocked));
+
+    gemm_tn(&a_t, &b, &mut backend, m, k, n);
+    blocked_gemm_tn(&a_t, m, &b, &mut blocked, m, k, n);
+    assert!(close(&backend, &blocked));
+
+    let a = a.iter().map(|x| *x as f32).collect::<Vec<f32>>();
+    let b = b.iter().map(|x| *x as f32).collect::<Vec<f32>>();
+    let (mut backend, mut blocked) = (vec![0.0f32; m * n], vec![0.0f32; m * n]);
+    gemm(&a, &b, &mut backend, m, k, n);
+    blocked_gemm(&a, &b, &mut blocked, m, k, n);
+    assert!(backend
+        .iter()
+        .zip(&blocked)
+        .all(|(x, y)| (x - y).abs() < 1e-4));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_matches_serial() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(13);
+    // Large enough that every kernel below crosses PARALLEL_THRESHOLD
+    let (m, k, n) = (97, 130, 61);
+    let mut random = |len: usize| {
+        (0..len)
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect::<Vec<f64>>()
+    };
+    let (a, b, a_t, b_t) = (random(m * k), random(k * n), random(k * m), random(n * k));
+
+    let close = |x: &[f64], y: &[f64]| x.iter().zip(y).all(|(x, y)| (x - y).abs() < 1e-12);
+    let (mut parallel, mut serial) = (vec![0.0; m * n], vec![0.0; m * n]);
+
+    gemm(&a, &b, &mut parallel, m, k, n);
+    blocked_gemm(&a, &b, &mut serial, m, k, n);
+    assert!(close(&parallel, &serial));
+
+    gemm_nt(&a, &b_t, &mut parallel, m, k, n);
+    blocked_gemm_nt(&a, &b_t, &mut serial, m, k, n);
+    assert!(close(&parallel, &serial));
+
+    gemm_tn(&a_t, &b, &mut parallel, m, k, n);
+    blocked_gemm_tn(&a_t, m, &b, &mut serial, m, k, n);
+    assert!(close(&parallel, &serial));
+
+    let (mut parallel, mut serial) = (vec![0.0; m * k], vec![0.0; m * k]);
+    transpose(&a, &mut parallel, m, k);
+    blocked_transpose(&a, &mut serial, m, k, 0..k);
+    assert_eq!(parallel, serial);
+
+    apply(&mut parallel, |x| *x = x.exp());
+    zip_apply(&mut parallel, &serial, |x, y| *x -= y.exp());
+    assert!(parallel.iter().all(|x| *x == 0.0));
+}
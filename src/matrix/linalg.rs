@@ -0,0 +1,405 @@
+use super::Matrix;
+use crate::float::{self, Float};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinalgError {
+    NotSquare { rows: usize, cols: usize },
+    Singular,
+    NotPositiveDefinite,
+}
+
+impl std::fmt::Display for LinalgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LinalgError::NotSquare { rows, cols } => {
+                write!(f, "Expected a square matrix, got {}x{}", rows, cols)
+            }
+            LinalgError::Singular => write!(f, "Matrix is singular or rank deficient"),
+            LinalgError::NotPositiveDefinite => {
+                write!(f, "Matrix is not symmetric positive definite")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinalgError {}
+
+// LU factorization with partial pivoting, P * A = L * U
+#[derive(Clone, Debug)]
+pub struct Lu<T = f64> {
+    // L below the diagonal (with an implicit unit diagonal) and U on and above it
+    factors: Matrix<T>,
+    // Row i of P * A is row pivots[i] of A
+    pivots: Vec<usize>,
+    swaps: usize,
+    tolerance: T,
+}
+
+impl<T: Float> Lu<T> {
+    pub fn l(&self) -> Matrix<T> {
+        let n = self.factors.rows;
+        let mut l = Matrix::eye(n);
+        for i in 0..n {
+            for j in 0..i {
+                l[(i, j)] = self.factors[(i, j)];
+            }
+        }
+        l
+    }
+
+    pub fn u(&self) -> Matrix<T> {
+        let n = self.factors.rows;
+        let mut u = Matrix::zero(n, n);
+        for i in 0..n {
+            for j in i..n {
+                u[(i, j)] = self.factors[(i, j)];
+            }
+        }
+        u
+    }
+
+    pub fn pivots(&self) -> &[usize] {
+        &self.pivots
+    }
+
+    pub fn determinant(&self) -> T {
+        let product = (0..self.factors.rows)
+            .map(|i| self.factors[(i, i)])
+            .fold(T::one(), |product, x| product * x);
+
+        if self.swaps.is_multiple_of(2) {
+            product
+        } else {
+            -product
+        }
+    }
+
+    pub fn is_singular(&self) -> bool {
+        (0..self.factors.rows).any(|i| self.factors[(i, i)].abs() <= self.tolerance)
+    }
+
+    // Solves A * x = b for every column of b
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        let n = self.factors.rows;
+        if b.rows != n {
+            panic!("Right hand side has {} rows, expected {}", b.rows, n);
+        }
+        if self.is_singular() {
+            return Err(LinalgError::Singular);
+        }
+
+        let mut x = Matrix::zero(n, b.cols);
+        for (i, pivot) in self.pivots.iter().enumerate() {
+            x.row_mut(i).copy_from_slice(b.row(*pivot));
+        }
+
+        for col in 0..b.cols {
+            // Forward substitution with the unit lower triangle
+            for i in 0..n {
+                let mut sum = x[(i, col)];
+                for j in 0..i {
+                    sum -= self.factors[(i, j)] * x[(j, col)];
+                }
+                x[(i, col)] = sum;
+            }
+            // Back substitution with the upper triangle
+            for i in (0..n).rev() {
+                let mut sum = x[(i, col)];
+                for j in i + 1..n {
+                    sum -= self.factors[(i, j)] * x[(j, col)];
+                }
+                x[(i, col)] = sum / self.factors[(i, i)];
+            }
+        }
+
+        Ok(x)
+    }
+
+    pub fn inverse(&self) -> Result<Matrix<T>, LinalgError> {
+        self.solve(&Matrix::eye(self.factors.rows))
+    }
+}
+
+// Householder QR, A = Q * R with Q orthogonal (rows x rows) and R upper triangular (rows x cols)
+#[derive(Clone, Debug)]
+pub struct Qr<T = f64> {
+    pub q: Matrix<T>,
+    pub r: Matrix<T>,
+}
+
+impl<T: Float> Matrix<T> {
+    pub fn lu(&self) -> Result<Lu<T>, LinalgError> {
+        self.check_square()?;
+
+        let n = self.rows;
+        let mut a = self.clone();
+        let mut pivots: Vec<usize> = (0..n).collect();
+        let mut swaps = 0;
+
+        for k in 0..n {
+            let mut pivot_row = k;
+            for i in k + 1..n {
+                if a[(i, k)].abs() > a[(pivot_row, k)].abs() {
+                    pivot_row = i;
+                }
+            }
+            if pivot_row != k {
+                for j in 0..n {
+                    a.data.swap(k * n + j, pivot_row * n + j);
+                }
+                pivots.swap(k, pivot_row);
+                swaps += 1;
+            }
+
+            let pivot = a[(k, k)];
+            if pivot == T::zero() {
+                continue;
+            }
+            for i in k + 1..n {
+                let factor = a[(i, k)] / pivot;
+                a[(i, k)] = factor;
+                for j in k + 1..n {
+                    let delta = factor * a[(k, j)];
+                    a[(i, j)] -= delta;
+                }
+            }
+        }
+
+        Ok(Lu {
+            factors: a,
+            pivots,
+            swaps,
+            tolerance: self.tolerance(),
+        })
+    }
+
+    pub fn qr(&self) -> Qr<T> {
+        let (m, n) = (self.rows, self.cols);
+        let mut q = Matrix::eye(m);
+        let mut r = self.clone();
+        let two: T = float::cast(2.0);
+
+        for k in 0..n.min(m.saturating_sub(1)) {
+            let norm = (k..m).map(|i| r[(i, k)] * r[(i, k)]).sum::<T>().sqrt();
+            if norm == T::zero() {
+                continue;
+            }
+
+            // Reflect column k onto -sign(r_kk) * norm * e_k, the sign avoids cancellation
+            let alpha = if r[(k, k)] > T::zero() { -norm } else { norm };
+            let mut v: Vec<T> = (k..m).map(|i| r[(i, k)]).collect();
+            v[0] -= alpha;
+            let v_norm = v.iter().map(|x| *x * *x).sum::<T>();
+            if v_norm == T::zero() {
+                continue;
+            }
+
+            for j in 0..n {
+                let dot = (0..v.len()).map(|i| v[i] * r[(k + i, j)]).sum::<T>();
+                let factor = two * dot / v_norm;
+                for (i, v) in v.iter().enumerate() {
+                    r[(k + i, j)] -= factor * *v;
+                }
+            }
+            for i in 0..m {
+                let dot = (0..v.len()).map(|l| q[(i, k + l)] * v[l]).sum::<T>();
+                let factor = two * dot / v_norm;
+                for (l, v) in v.iter().enumerate() {
+                    q[(i, k + l)] -= factor * *v;
+                }
+            }
+        }
+
+        for i in 1..m {
+            for j in 0..i.min(n) {
+                r[(i, j)] = T::zero();
+            }
+        }
+
+        Qr { q, r }
+    }
+
+    // Lower triangular L with A = L * L^T
+    pub fn cholesky(&self) -> Result<Matrix<T>, LinalgError> {
+        self.check_square()?;
+
+        let n = self.rows;
+        let tolerance = self.tolerance();
+        let mut l = Matrix::zero(n, n);
+
+        for j in 0..n {
+            for i in j + 1..n {
+                if (self[(i, j)] - self[(j, i)]).abs() > tolerance {
+                    return Err(LinalgError::NotPositiveDefinite);
+                }
+            }
+
+            let diagonal = self[(j, j)] - (0..j).map(|k| l[(j, k)] * l[(j, k)]).sum::<T>();
+            if diagonal <= T::zero() || diagonal.is_nan() {
+                return Err(LinalgError::NotPositiveDefinite);
+            }
+            l[(j, j)] = diagonal.sqrt();
+
+            for i in j + 1..n {
+                let sum = (0..j).map(|k| l[(i, k)] * l[(j, k)]).sum::<T>();
+                l[(i, j)] = (self[(i, j)] - sum) / l[(j, j)];
+            }
+        }
+
+        Ok(l)
+    }
+
+    pub fn determinant(&self) -> Result<T, LinalgError> {
+        Ok(self.lu()?.determinant())
+    }
+
+    pub fn inverse(&self) -> Result<Matrix<T>, LinalgError> {
+        self.lu()?.inverse()
+    }
+
+    // Solves self * x = b, b may hold several right hand sides as columns
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        self.lu()?.solve(b)
+    }
+
+    // Minimizes |self * x - b| through QR, self needs at least as many rows as columns and full rank
+    pub fn least_squares(&self, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        if b.rows != self.rows {
+            panic!(
+                "Right hand side has {} rows, expected {}",
+                b.rows, self.rows
+            );
+        }
+        if self.rows < self.cols {
+            return Err(LinalgError::Singular);
+        }
+
+        let n = self.cols;
+        let tolerance = self.tolerance();
+        let Qr { q, r } = self.qr();
+        if (0..n).any(|i| r[(i, i)].abs() <= tolerance) {
+            return Err(LinalgError::Singular);
+        }
+
+        let mut x = q.transposed_dot(b);
+        x.data.truncate(n * b.cols);
+        x.rows = n;
+
+        for col in 0..b.cols {
+            for i in (0..n).rev() {
+                let mut sum = x[(i, col)];
+                for j in i + 1..n {
+                    sum -= r[(i, j)] * x[(j, col)];
+                }
+                x[(i, col)] = sum / r[(i, i)];
+            }
+        }
+
+        Ok(x)
+    }
+
+    fn check_square(&self) -> Result<(), LinalgError> {
+        if self.rows != self.cols {
+            return Err(LinalgError::NotSquare {
+                rows: self.rows,
+                cols: self.cols,
+            });
+        }
+        Ok(())
+    }
+
+    // Values this close to zero are treated as zero when deciding whether a matrix is singular
+    fn tolerance(&self) -> T {
+        let max = self.data.iter().fold(T::zero(), |max, x| max.max(x.abs()));
+        let size: T = float::cast(self.rows.max(self.cols) as f64);
+        T::epsilon() * size * max
+    }
+}
+
+#[cfg(test)]
+fn assert_close(a: &Matrix, b: &Matrix) {
+    assert_eq!((a.rows, a.cols), (b.rows, b.cols));
+    for (x, y) in a.data.iter().zip(&b.data) {
+        assert!((x - y).abs() < 1e-10, "{} != {}\n{}{}", x, y, a, b);
+    }
+}
+
+#[test]
+fn lu() {
+    let a = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 10.0], 3, 3);
+    let lu = a.lu().unwrap();
+
+    let mut permuted = Matrix::zero(3, 3);
+    for (i, pivot) in lu.pivots().iter().enumerate() {
+        permuted.row_mut(i).copy_from_slice(a.row(*pivot));
+    }
+    assert_close(&lu.l().dot_multiply(&lu.u()), &permuted);
+    assert!((lu.determinant() - -3.0).abs() < 1e-10);
+    assert_close(&a.inverse().unwrap().dot_multiply(&a), &Matrix::eye(3));
+
+    let b = Matrix::from_vec(&vec![1.0, 0.0, 2.0, 1.0, 3.0, 1.0], 3, 2);
+    let x = a.solve(&b).unwrap();
+    assert_close(&a.dot_multiply(&x), &b);
+}
+
+#[test]
+fn singular() {
+    let a = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 1.0, 0.0, 1.0], 3, 3);
+
+    assert!(a.lu().unwrap().is_singular());
+    assert_eq!(a.determinant(), Ok(0.0));
+    assert_eq!(a.inverse(), Err(LinalgError::Singular));
+    assert_eq!(
+        Matrix::<f64>::zero(2, 3).inverse(),
+        Err(LinalgError::NotSquare { rows: 2, cols: 3 })
+    );
+}
+
+#[test]
+fn qr() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let a: Matrix = Matrix::random(5, 3, &mut StdRng::seed_from_u64(4));
+    let Qr { q, r } = a.qr();
+
+    assert_eq!((q.rows, q.cols, r.rows, r.cols), (5, 5, 5, 3));
+    assert_close(&q.dot_multiply(&r), &a);
+    assert_close(&q.transposed_dot(&q), &Matrix::eye(5));
+    for i in 1..5 {
+        for j in 0..i.min(3) {
+            assert_eq!(r[(i, j)], 0.0);
+        }
+    }
+}
+
+#[test]
+fn cholesky() {
+    let a = Matrix::from_vec(
+        &vec![4.0, 12.0, -16.0, 12.0, 37.0, -43.0, -16.0, -43.0, 98.0],
+        3,
+        3,
+    );
+    let l = a.cholesky().unwrap();
+
+    assert_eq!(l.data, vec![2.0, 0.0, 0.0, 6.0, 1.0, 0.0, -8.0, 5.0, 3.0]);
+    assert_close(&l.dot_transposed(&l), &a);
+
+    let indefinite = Matrix::from_vec(&vec![1.0, 2.0, 2.0, 1.0], 2, 2);
+    assert_eq!(indefinite.cholesky(), Err(LinalgError::NotPositiveDefinite));
+    let asymmetric = Matrix::from_vec(&vec![2.0, 1.0, 0.0, 2.0], 2, 2);
+    assert_eq!(asymmetric.cholesky(), Err(LinalgError::NotPositiveDefinite));
+}
+
+#[test]
+fn least_squares() {
+    // y = 2x + 1 sampled without noise, with a column of ones for the intercept
+    let xs = [0.0, 1.0, 2.0, 3.0, 4.0];
+    let a = Matrix::from_vec_2d(xs.iter().map(|x| vec![*x, 1.0]).collect());
+    let b = Matrix::from_vec(&xs.iter().map(|x| 2.0 * x + 1.0).collect(), 5, 1);
+
+    let coefficients = a.least_squares(&b).unwrap();
+    assert_close(&coefficients, &Matrix::from_vec(&vec![2.0, 1.0], 2, 1));
+
+    let rank_deficient = Matrix::from_vec_2d(xs.iter().map(|x| vec![*x, 2.0 * x]).collect());
+    assert_eq!(rank_deficient.least_squares(&b), Err(LinalgError::Singular));
+}
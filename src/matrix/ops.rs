@@ -0,0 +1,258 @@
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+
+use super::{kernels, Matrix};
+use crate::float::Float;
+
+// Owned left operands reuse their buffer, borrowed ones are cloned first.
+// Mul between two matrices is the elementwise product, use dot_multiply for the matrix product.
+macro_rules! elementwise_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $assign_op:tt) => {
+        impl<T: Float> $assign_trait<&Matrix<T>> for Matrix<T> {
+            fn $assign_method(&mut self, other: &Matrix<T>) {
+                self.check_same_shape(other);
+                kernels::zip_apply(&mut self.data, &other.data, |x, y| *x $assign_op y);
+            }
+        }
+
+        impl<T: Float> $assign_trait<Matrix<T>> for Matrix<T> {
+            fn $assign_method(&mut self, other: Matrix<T>) {
+                *self $assign_op &other;
+            }
+        }
+
+        impl<T: Float> $trait<&Matrix<T>> for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(mut self, other: &Matrix<T>) -> Matrix<T> {
+                self $assign_op other;
+                self
+            }
+        }
+
+        impl<T: Float> $trait<Matrix<T>> for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(mut self, other: Matrix<T>) -> Matrix<T> {
+                self $assign_op &other;
+                self
+            }
+        }
+
+        impl<T: Float> $trait<&Matrix<T>> for &Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: &Matrix<T>) -> Matrix<T> {
+                let mut result = self.clone();
+                result $assign_op other;
+                result
+            }
+        }
+
+        impl<T: Float> $trait<Matrix<T>> for &Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: Matrix<T>) -> Matrix<T> {
+                let mut result = self.clone();
+                result $assign_op &other;
+                result
+            }
+        }
+    };
+}
+
+macro_rules! scalar_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $assign_op:tt) => {
+        impl<T: Float> $assign_trait<T> for Matrix<T> {
+            fn $assign_method(&mut self, scalar: T) {
+                kernels::apply(&mut self.data, |x| *x $assign_op scalar);
+            }
+        }
+
+        impl<T: Float> $trait<T> for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(mut self, scalar: T) -> Matrix<T> {
+                self $assign_op scalar;
+                self
+            }
+        }
+
+        impl<T: Float> $trait<T> for &Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, scalar: T) -> Matrix<T> {
+                let mut result = self.clone();
+                result $assign_op scalar;
+                result
+            }
+        }
+    };
+}
+
+// Lets a scalar sit on the left, as in 0.5 * matrix. Coherence rules only allow this per concrete type.
+macro_rules! left_scalar_mul {
+    ($($float:ty),*) => {
+        $(
+            impl Mul<Matrix<$float>> for $float {
+                type Output = Matrix<$float>;
+
+                fn mul(self, matrix: Matrix<$float>) -> Matrix<$float> {
+                    matrix * self
+                }
+            }
+
+            impl Mul<&Matrix<$float>> for $float {
+                type Output = Matrix<$float>;
+
+                fn mul(self, matrix: &Matrix<$float>) -> Matrix<$float> {
+                    matrix * self
+                }
+            }
+        )*
+    };
+}
+
+elementwise_op!(Add, add, AddAssign, add_assign, +=);
+elementwise_op!(Sub, sub, SubAssign, sub_assign, -=);
+elementwise_op!(Mul, mul, MulAssign, mul_assign, *=);
+
+scalar_op!(Add, add, AddAssign, add_assign, +=);
+scalar_op!(Sub, sub, SubAssign, sub_assign, -=);
+scalar_op!(Mul, mul, MulAssign, mul_assign, *=);
+scalar_op!(Div, div, DivAssign, div_assign, /=);
+
+left_scalar_mul!(f32, f64);
+
+impl<T: Float> Neg for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn neg(mut self) -> Matrix<T> {
+        self.map_inplace(&|x| -x);
+        self
+    }
+}
+
+impl<T: Float> Neg for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn neg(self) -> Matrix<T> {
+        self.map(&|x| -x)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        if row >= self.rows || col >= self.cols {
+            panic!(
+                "Index ({}, {}) out of bounds for a {}x{} matrix",
+                row, col, self.rows, self.cols
+            );
+        }
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        if row >= self.rows || col >= self.cols {
+            panic!(
+                "Index ({}, {}) out of bounds for a {}x{} matrix",
+                row, col, self.rows, self.cols
+            );
+        }
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+#[test]
+fn elementwise_operators() {
+    let a = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0], 2, 2);
+    let b = Matrix::from_vec(&vec![0.5, -1.0, 2.0, 0.0], 2, 2);
+
+    // Add is in scope here, so a.add(&b) would resolve to the consuming operator
+    assert_eq!(&a + &b, Matrix::add(&a, &b));
+    assert_eq!(&a - &b, a.subtract(&b));
+    assert_eq!(&a * &b, a.multiply(&b));
+    assert_eq!((a.clone() + b.clone()).data, vec![1.5, 1.0, 5.0, 4.0]);
+    assert_eq!((&a - b.clone()).data, vec![0.5, 3.0, 1.0, 4.0]);
+    assert_eq!((-&a).data, vec![-1.0, -2.0, -3.0, -4.0]);
+    assert_eq!(-a.clone(), -&a);
+
+    let mut c = a.clone();
+    c += &b;
+    c -= b.clone();
+    assert_eq!(c, a);
+    c *= &b;
+    assert_eq!(c, a.multiply(&b));
+}
+
+#[test]
+fn scalar_operators() {
+    let a = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0], 2, 2);
+
+    assert_eq!((&a * 2.0).data, vec![2.0, 4.0, 6.0, 8.0]);
+    assert_eq!(2.0 * &a, &a * 2.0);
+    assert_eq!((a.clone() / 2.0).data, vec![0.5, 1.0, 1.5, 2.0]);
+    assert_eq!((&a + 1.0).data, vec![2.0, 3.0, 4.0, 5.0]);
+    assert_eq!((a.clone() - 1.0).data, vec![0.0, 1.0, 2.0, 3.0]);
+
+    let mut b = a.clone();
+    b *= 3.0;
+    b /= 3.0;
+    b += 1.0;
+    b -= 1.0;
+    assert_eq!(b, a);
+
+    let single = Matrix::from_vec(&vec![1.5f32], 1, 1);
+    assert_eq!((2.0f32 * single).data, vec![3.0f32]);
+}
+
+#[test]
+fn operators_match_in_place_methods() {
+    let a = Matrix::from_vec(&vec![1.0, -2.0, 0.5, 4.0, 3.0, -1.5], 2, 3);
+    let b = Matrix::from_vec(&vec![2.0, 0.25, -1.0, 0.0, 1.0, 8.0], 2, 3);
+
+    let (mut operator, mut method) = (a.clone(), a.clone());
+    operator += &b;
+    method.add_assign(&b);
+    assert_eq!(operator, method);
+    operator -= &a;
+    method.subtract_assign(&a);
+    assert_eq!(operator, method);
+    operator *= &a;
+    method.multiply_assign(&a);
+    assert_eq!(operator, method);
+    operator *= 0.5;
+    method.scale_inplace(0.5);
+    assert_eq!(operator, method);
+
+    // Differences of weights before and after an update, as the gradient clipping tests take them
+    assert_eq!(&(&a - &b) * 2.0, a.subtract(&b).map(&|x| x * 2.0));
+}
+
+#[test]
+#[should_panic]
+fn operator_shape_mismatch() {
+    let a: Matrix = Matrix::zero(2, 2);
+    let _ = &a + &Matrix::zero(2, 3);
+}
+
+#[test]
+fn indexing() {
+    let mut matrix = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3);
+
+    assert_eq!(matrix[(1, 0)], 4.0);
+    matrix[(0, 2)] = -3.0;
+    assert_eq!(matrix.data, vec![1.0, 2.0, -3.0, 4.0, 5.0, 6.0]);
+}
+
+#[test]
+#[should_panic]
+fn index_out_of_bounds() {
+    let matrix: Matrix = Matrix::zero(3, 2);
+    let _ = matrix[(0, 2)];
+}
@@ -0,0 +1,129 @@
+use rand::Rng;
+
+use super::Matrix;
+use crate::float::{self, Float};
+
+// Truncated normal draws further than this many standard deviations from the mean are redrawn
+const TRUNCATION: f64 = 2.0;
+
+// Every constructor takes the caller's rng, so a seeded StdRng gives reproducible matrices
+impl<T: Float> Matrix<T> {
+    // Uniform in [low, high)
+    pub fn uniform<R: Rng + ?Sized>(
+        rows: usize,
+        cols: usize,
+        low: T,
+        high: T,
+        rng: &mut R,
+    ) -> Self {
+        if low >= high {
+            panic!("Uniform range [{}, {}) is empty", low, high);
+        }
+        Matrix::sample(rows, cols, || float::uniform(rng, low, high))
+    }
+
+    pub fn normal<R: Rng + ?Sized>(rows: usize, cols: usize, mean: T, std: T, rng: &mut R) -> Self {
+        check_std(std);
+        Matrix::sample(rows, cols, || float::normal(rng, mean, std))
+    }
+
+    // Normal draws restricted to mean +- 2 std, the usual choice for weight initialization
+    pub fn truncated_normal<R: Rng + ?Sized>(
+        rows: usize,
+        cols: usize,
+        mean: T,
+        std: T,
+        rng: &mut R,
+    ) -> Self {
+        check_std(std);
+        let bound = std * float::cast(TRUNCATION);
+        Matrix::sample(rows, cols, || loop {
+            let x = float::normal(rng, mean, std);
+            if (x - mean).abs() <= bound {
+                return x;
+            }
+        })
+    }
+
+    // Each cell is 1 with probability p and 0 otherwise, e.g. a dropout keep mask
+    pub fn bernoulli<R: Rng + ?Sized>(rows: usize, cols: usize, p: f64, rng: &mut R) -> Self {
+        if !(0.0..=1.0).contains(&p) {
+            panic!("Bernoulli probability {} is outside [0, 1]", p);
+        }
+        Matrix::sample(rows, cols, || {
+            if rng.gen_bool(p) {
+                T::one()
+            } else {
+                T::zero()
+            }
+        })
+    }
+
+    fn sample(rows: usize, cols: usize, draw: impl FnMut() -> T) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: std::iter::repeat_with(draw).take(rows * cols).collect(),
+        }
+    }
+}
+
+fn check_std<T: Float>(std: T) {
+    if std < T::zero() || !std.is_finite() {
+        panic!("Standard deviation {} must be finite and non-negative", std);
+    }
+}
+
+#[cfg(test)]
+fn moments(matrix: &Matrix) -> (f64, f64) {
+    let n = matrix.data.len() as f64;
+    let mean = matrix.data.iter().sum::<f64>() / n;
+    let variance = matrix.data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+#[test]
+fn distributions() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(21);
+
+    let uniform = Matrix::uniform(200, 200, 2.0, 4.0, &mut rng);
+    assert!(uniform.data.iter().all(|x| (2.0..4.0).contains(x)));
+    let (mean, _) = moments(&uniform);
+    assert!((mean - 3.0).abs() < 0.02);
+
+    let normal = Matrix::normal(200, 200, -1.0, 0.5, &mut rng);
+    let (mean, std) = moments(&normal);
+    assert!((mean + 1.0).abs() < 0.02);
+    assert!((std - 0.5).abs() < 0.02);
+
+    let truncated = Matrix::truncated_normal(200, 200, 0.0, 0.5, &mut rng);
+    assert!(truncated.data.iter().all(|x: &f64| x.abs() <= 1.0));
+    // Cutting the tails at two standard deviations leaves about 88% of the spread
+    let (_, std) = moments(&truncated);
+    assert!((std - 0.44).abs() < 0.02);
+
+    let mask: Matrix = Matrix::bernoulli(200, 200, 0.8, &mut rng);
+    assert!(mask.data.iter().all(|x| *x == 0.0 || *x == 1.0));
+    assert!((moments(&mask).0 - 0.8).abs() < 0.01);
+}
+
+#[test]
+fn seeded_and_eye() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let draw = |seed| Matrix::<f32>::normal(3, 4, 0.0, 1.0, &mut StdRng::seed_from_u64(seed));
+    assert_eq!(draw(5), draw(5));
+    assert_ne!(draw(5), draw(6));
+
+    let eye: Matrix = Matrix::eye(3);
+    assert_eq!(eye.data, vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+    assert!(Matrix::<f64>::eye(0).data.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn negative_std() {
+    Matrix::<f64>::normal(2, 2, 0.0, -1.0, &mut rand::thread_rng());
+}
@@ -0,0 +1,541 @@
+use super::{kernels, Matrix};
+use crate::float::Float;
+
+// Compressed sparse row storage: the entries of row i are values[row_offsets[i]..row_offsets[i + 1]],
+// sitting in the columns listed at the same positions of col_indices
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix<T = f64> {
+    rows: usize,
+    cols: usize,
+    row_offsets: Vec<usize>,
+    col_indices: Vec<usize>,
+    values: Vec<T>,
+}
+
+// Compressed sparse column storage, the column-major counterpart of CsrMatrix
+#[derive(Clone, Debug, PartialEq)]
+pub struct CscMatrix<T = f64> {
+    rows: usize,
+    cols: usize,
+    col_offsets: Vec<usize>,
+    row_indices: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T: Float> CsrMatrix<T> {
+    pub fn new(
+        rows: usize,
+        cols: usize,
+        row_offsets: Vec<usize>,
+        col_indices: Vec<usize>,
+        values: Vec<T>,
+    ) -> Self {
+        check_compressed(rows, cols, &row_offsets, &col_indices, values.len());
+        CsrMatrix {
+            rows,
+            cols,
+            row_offsets,
+            col_indices,
+            values,
+        }
+    }
+
+    // Duplicate (row, col) entries are summed
+    pub fn from_triplets(rows: usize, cols: usize, triplets: &[(usize, usize, T)]) -> Self {
+        let (row_offsets, col_indices, values) = compress(rows, cols, triplets);
+        CsrMatrix {
+            rows,
+            cols,
+            row_offsets,
+            col_indices,
+            values,
+        }
+    }
+
+    pub fn from_dense(matrix: &Matrix<T>) -> Self {
+        let (row_offsets, col_indices, values) =
+            compress_dense(matrix.rows, matrix.cols, |i, j| matrix[(i, j)]);
+        CsrMatrix {
+            rows: matrix.rows,
+            cols: matrix.cols,
+            row_offsets,
+            col_indices,
+            values,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    // Number of stored entries
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn row_offsets(&self) -> &[usize] {
+        &self.row_offsets
+    }
+
+    pub fn col_indices(&self) -> &[usize] {
+        &self.col_indices
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn to_dense(&self) -> Matrix<T> {
+        let mut matrix = Matrix::zero(self.rows, self.cols);
+        for row in 0..self.rows {
+            for p in self.row_offsets[row]..self.row_offsets[row + 1] {
+                matrix[(row, self.col_indices[p])] = self.values[p];
+            }
+        }
+        matrix
+    }
+
+    pub fn to_csc(&self) -> CscMatrix<T> {
+        let (col_offsets, row_indices, values) = transpose_compressed(
+            self.rows,
+            self.cols,
+            &self.row_offsets,
+            &self.col_indices,
+            &self.values,
+        );
+        CscMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            col_offsets,
+            row_indices,
+            values,
+        }
+    }
+
+    // self (sparse) * other (dense)
+    pub fn dot_dense(&self, other: &Matrix<T>) -> Matrix<T> {
+        if self.cols != other.rows {
+            panic!("Size mismatch when dot multiplying matrices. Matrix A columns must match Matrix B rows.");
+        }
+
+        let mut result = Matrix::zero(self.rows, other.cols);
+        for row in 0..self.rows {
+            let result_row = result.row_mut(row);
+            for p in self.row_offsets[row]..self.row_offsets[row + 1] {
+                kernels::axpy(self.values[p], other.row(self.col_indices[p]), result_row);
+            }
+        }
+        result
+    }
+}
+
+impl<T: Float> CscMatrix<T> {
+    pub fn new(
+        rows: usize,
+        cols: usize,
+        col_offsets: Vec<usize>,
+        row_indices: Vec<usize>,
+        values: Vec<T>,
+    ) -> Self {
+        check_compressed(cols, rows, &col_offsets, &row_indices, values.len());
+        CscMatrix {
+            rows,
+            cols,
+            col_offsets,
+            row_indices,
+            values,
+        }
+    }
+
+    // Duplicate (row, col) entries are summed
+    pub fn from_triplets(rows: usize, cols: usize, triplets: &[(usize, usize, T)]) -> Self {
+        let transposed: Vec<(usize, usize, T)> = triplets
+            .iter()
+            .map(|(row, col, value)| (*col, *row, *value))
+            .collect();
+        let (col_offsets, row_indices, values) = compress(cols, rows, &transposed);
+        CscMatrix {
+            rows,
+            cols,
+            col_offsets,
+            row_indices,
+            values,
+        }
+    }
+
+    // Overwrites self with other, reusing the allocations
+    pub(crate) fn copy_from(&mut self, other: &CscMatrix<T>) {
+        self.rows = other.rows;
+        self.cols = other.cols;
+        self.col_offsets.clone_from(&other.col_offsets);
+        self.row_indices.clone_from(&other.row_indices);
+        self.values.clone_from(&other.values);
+    }
+
+    // A single column of length len, the usual shape of a sparse feature vector
+    pub fn column_vector(len: usize, entries: &[(usize, T)]) -> Self {
+        let triplets: Vec<(usize, usize, T)> = entries
+            .iter()
+            .map(|(row, value)| (*row, 0, *value))
+            .collect();
+        CscMatrix::from_triplets(len, 1, &triplets)
+    }
+
+    pub fn from_dense(matrix: &Matrix<T>) -> Self {
+        let (col_offsets, row_indices, values) =
+            compress_dense(matrix.cols, matrix.rows, |j, i| matrix[(i, j)]);
+        CscMatrix {
+            rows: matrix.rows,
+            cols: matrix.cols,
+            col_offsets,
+            row_indices,
+            values,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn col_offsets(&self) -> &[usize] {
+        &self.col_offsets
+    }
+
+    pub fn row_indices(&self) -> &[usize] {
+        &self.row_indices
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn to_dense(&self) -> Matrix<T> {
+        let mut matrix = Matrix::zero(self.rows, self.cols);
+        for col in 0..self.cols {
+            for p in self.col_offsets[col]..self.col_offsets[col + 1] {
+                matrix[(self.row_indices[p], col)] = self.values[p];
+            }
+        }
+        matrix
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix<T> {
+        let (row_offsets, col_indices, values) = transpose_compressed(
+            self.cols,
+            self.rows,
+            &self.col_offsets,
+            &self.row_indices,
+            &self.values,
+        );
+        CsrMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            row_offsets,
+            col_indices,
+            values,
+        }
+    }
+}
+
+impl<T: Float> Matrix<T> {
+    // self (dense) * other (sparse), only touching the columns of self that meet a stored entry
+    pub fn dot_csc(&self, other: &CscMatrix<T>) -> Matrix<T> {
+        let mut result = Matrix::zero(self.rows, other.cols);
+        self.dot_csc_into(other, &mut result);
+        result
+    }
+
+    pub fn dot_csc_into(&self, other: &CscMatrix<T>, output: &mut Matrix<T>) {
+        if self.cols != other.rows {
+            panic!("Size mismatch when dot multiplying matrices. Matrix A columns must match Matrix B rows.");
+        }
+
+        output.reset(self.rows, other.cols);
+        if other.cols == 0 {
+            return;
+        }
+        for (row, output) in self
+            .iter_rows()
+            .zip(output.data.chunks_exact_mut(other.cols))
+        {
+            for (col, output) in output.iter_mut().enumerate() {
+                let entries = other.col_offsets[col]..other.col_offsets[col + 1];
+                for (inner, value) in other.row_indices[entries.clone()]
+                    .iter()
+                    .zip(&other.values[entries])
+                {
+                    *output += row[*inner] * *value;
+                }
+            }
+        }
+    }
+
+    // self (m x 1) * the transpose of a sparse column, keeping only the columns of its stored
+    // entries, so output is m x nnz
+    pub(crate) fn outer_csc_into(&self, column: &CscMatrix<T>, output: &mut Matrix<T>) {
+        if self.cols != 1 || column.cols != 1 {
+            panic!("Outer products need two column vectors");
+        }
+
+        output.reset(self.rows, column.nnz());
+        for (x, output) in self
+            .data
+            .iter()
+            .zip(output.data.chunks_exact_mut(column.nnz().max(1)))
+        {
+            for (output, value) in output.iter_mut().zip(&column.values) {
+                *output = *x * *value;
+            }
+        }
+    }
+
+    // Adds column k of values to column columns[k] of self
+    pub(crate) fn add_to_columns(&mut self, columns: &[usize], values: &Matrix<T>) {
+        if values.rows != self.rows || values.cols != columns.len() {
+            panic!("Size mismatch when adding columns to a matrix");
+        }
+
+        for row in 0..self.rows {
+            let (target, values) = (self.row_mut(row), values.row(row));
+            for (col, value) in columns.iter().zip(values) {
+                target[*col] += *value;
+            }
+        }
+    }
+
+    // Whether add_to_columns would leave every element finite, without writing anything
+    pub(crate) fn columns_sum_is_finite(&self, columns: &[usize], values: &Matrix<T>) -> bool {
+        if values.rows != self.rows || values.cols != columns.len() {
+            panic!("Size mismatch when adding columns to a matrix");
+        }
+
+        (0..self.rows).all(|row| {
+            let (target, values) = (self.row(row), values.row(row));
+            columns
+                .iter()
+                .zip(values)
+                .all(|(col, value)| (target[*col] + *value).is_finite())
+        })
+    }
+}
+
+impl<T: Float> From<&CsrMatrix<T>> for Matrix<T> {
+    fn from(matrix: &CsrMatrix<T>) -> Self {
+        matrix.to_dense()
+    }
+}
+
+impl<T: Float> From<&CscMatrix<T>> for Matrix<T> {
+    fn from(matrix: &CscMatrix<T>) -> Self {
+        matrix.to_dense()
+    }
+}
+
+// The helpers below are written for CSR, CSC reuses them by swapping the roles of rows and columns
+
+fn check_compressed(major: usize, minor: usize, offsets: &[usize], indices: &[usize], nnz: usize) {
+    if offsets.len() != major + 1 || offsets[0] != 0 || offsets[major] != nnz {
+        panic!("Offsets do not describe {} stored entries", nnz);
+    }
+    if indices.len() != nnz {
+        panic!("Number of indices does not match the number of values");
+    }
+    if offsets.windows(2).any(|pair| pair[0] > pair[1]) {
+        panic!("Offsets must be non-decreasing");
+    }
+    if indices.iter().any(|index| *index >= minor) {
+        panic!("Index out of bounds for {} columns", minor);
+    }
+}
+
+type Compressed<T> = (Vec<usize>, Vec<usize>, Vec<T>);
+
+fn compress<T: Float>(major: usize, minor: usize, triplets: &[(usize, usize, T)]) -> Compressed<T> {
+    let mut sorted = triplets.to_vec();
+    sorted.sort_by_key(|(i, j, _)| (*i, *j));
+
+    let mut offsets = vec![0; major + 1];
+    let mut indices: Vec<usize> = Vec::with_capacity(sorted.len());
+    let mut values: Vec<T> = Vec::with_capacity(sorted.len());
+    let mut last = None;
+
+    for (i, j, value) in sorted {
+        if i >= major || j >= minor {
+            panic!(
+                "Entry ({}, {}) out of bounds for a {}x{} matrix",
+                i, j, major, minor
+            );
+        }
+        if last == Some((i, j)) {
+            *values.last_mut().unwrap() += value;
+            continue;
+        }
+        offsets[i + 1] += 1;
+        indices.push(j);
+        values.push(value);
+        last = Some((i, j));
+    }
+
+    for i in 0..major {
+        offsets[i + 1] += offsets[i];
+    }
+    (offsets, indices, values)
+}
+
+fn compress_dense<T: Float>(
+    major: usize,
+    minor: usize,
+    get: impl Fn(usize, usize) -> T,
+) -> Compressed<T> {
+    let mut offsets = Vec::with_capacity(major + 1);
+    let mut indices = vec![];
+    let mut values = vec![];
+
+    offsets.push(0);
+    for i in 0..major {
+        for j in 0..minor {
+            let value = get(i, j);
+            if value != T::zero() {
+                indices.push(j);
+                values.push(value);
+            }
+        }
+        offsets.push(values.len());
+    }
+    (offsets, indices, values)
+}
+
+// Counting sort of the entries by their minor index, turning CSR into CSC and back
+fn transpose_compressed<T: Float>(
+    major: usize,
+    minor: usize,
+    offsets: &[usize],
+    indices: &[usize],
+    values: &[T],
+) -> Compressed<T> {
+    let mut transposed_offsets = vec![0; minor + 1];
+    for index in indices {
+        transposed_offsets[index + 1] += 1;
+    }
+    for j in 0..minor {
+        transposed_offsets[j + 1] += transposed_offsets[j];
+    }
+
+    let mut next = transposed_offsets.clone();
+    let mut transposed_indices = vec![0; values.len()];
+    let mut transposed_values = vec![T::zero(); values.len()];
+    for i in 0..major {
+        for p in offsets[i]..offsets[i + 1] {
+            let slot = next[indices[p]];
+            transposed_indices[slot] = i;
+            transposed_values[slot] = values[p];
+            next[indices[p]] += 1;
+        }
+    }
+    (transposed_offsets, transposed_indices, transposed_values)
+}
+
+#[test]
+fn conversions() {
+    let dense = Matrix::from_vec(&vec![0.0, 2.0, 0.0, 1.0, 0.0, 0.0, 0.0, 3.0, 4.0], 3, 3);
+
+    let csr = CsrMatrix::from_dense(&dense);
+    assert_eq!(csr.row_offsets(), &[0, 1, 2, 4]);
+    assert_eq!(csr.col_indices(), &[1, 0, 1, 2]);
+    assert_eq!(csr.values(), &[2.0, 1.0, 3.0, 4.0]);
+
+    let csc = csr.to_csc();
+    assert_eq!(csc, CscMatrix::from_dense(&dense));
+    assert_eq!(csc.col_offsets(), &[0, 1, 3, 4]);
+    assert_eq!(csc.row_indices(), &[1, 0, 2, 2]);
+    assert_eq!(csc.to_csr(), csr);
+
+    assert_eq!(csr.to_dense(), dense);
+    assert_eq!(Matrix::from(&csc), dense);
+}
+
+#[test]
+fn triplets() {
+    let triplets = [(2, 0, 1.0), (0, 1, 2.0), (2, 0, 0.5), (1, 2, -1.0)];
+
+    let csr = CsrMatrix::from_triplets(3, 3, &triplets);
+    let csc = CscMatrix::from_triplets(3, 3, &triplets);
+
+    assert_eq!(csr.nnz(), 3);
+    assert_eq!(
+        csr.to_dense().data,
+        vec![0.0, 2.0, 0.0, 0.0, 0.0, -1.0, 1.5, 0.0, 0.0]
+    );
+    assert_eq!(csc.to_dense(), csr.to_dense());
+    assert_eq!(
+        CscMatrix::column_vector(4, &[(3, 1.0), (1, 2.0)])
+            .to_dense()
+            .data,
+        vec![0.0, 2.0, 0.0, 1.0]
+    );
+}
+
+#[test]
+#[should_panic]
+fn invalid_offsets() {
+    CsrMatrix::new(2, 2, vec![0, 2, 1], vec![0, 1], vec![1.0, 2.0]);
+}
+
+#[test]
+fn sparse_products() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(6);
+    let dense: Matrix = Matrix::random(4, 6, &mut rng);
+    let sparse = CsrMatrix::from_triplets(3, 4, &[(0, 1, 2.0), (2, 0, -1.0), (2, 3, 0.5)]);
+    let features = CscMatrix::from_triplets(6, 2, &[(5, 0, 1.0), (0, 1, 3.0), (2, 1, 1.0)]);
+
+    let close = |a: &Matrix, b: &Matrix| {
+        a.data
+            .iter()
+            .zip(&b.data)
+            .all(|(x, y)| (x - y).abs() < 1e-12)
+    };
+
+    let product = sparse.dot_dense(&dense);
+    assert_eq!((product.rows, product.cols), (3, 6));
+    assert!(close(&product, &sparse.to_dense().dot_multiply(&dense)));
+
+    let product = dense.dot_csc(&features);
+    assert_eq!((product.rows, product.cols), (4, 2));
+    assert!(close(&product, &dense.dot_multiply(&features.to_dense())));
+}
+
+#[test]
+fn sparse_column_updates() {
+    let column = CscMatrix::column_vector(5, &[(1, 2.0), (4, -1.0)]);
+    let gradients = Matrix::from_vec(&vec![1.0, 0.5, -3.0], 3, 1);
+
+    let mut outer = Matrix::zero(0, 0);
+    gradients.outer_csc_into(&column, &mut outer);
+    assert_eq!(
+        outer,
+        Matrix::from_vec(&vec![2.0, -1.0, 1.0, -0.5, -6.0, 3.0], 3, 2)
+    );
+
+    // Only the columns of the stored entries change
+    let mut weights: Matrix = Matrix::zero(3, 5);
+    weights.add_to_columns(column.row_indices(), &outer);
+    assert_eq!(weights, gradients.dot_transposed(&column.to_dense()));
+
+    let mut output = Matrix::zero(0, 0);
+    weights.dot_csc_into(&column, &mut output);
+    assert_eq!(output, weights.dot_multiply(&column.to_dense()));
+}
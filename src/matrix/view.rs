@@ -0,0 +1,301 @@
+use std::ops::Range;
+
+use super::Matrix;
+use crate::float::Float;
+
+// Borrowed rectangular window into a matrix, rows are stride elements apart in data
+#[derive(Clone, Copy, Debug)]
+pub struct MatrixView<'a, T = f64> {
+    pub rows: usize,
+    pub cols: usize,
+    stride: usize,
+    data: &'a [T],
+}
+
+impl<'a, T: Float> MatrixView<'a, T> {
+    pub fn get(&self, row: usize, col: usize) -> T {
+        if row >= self.rows || col >= self.cols {
+            panic!(
+                "Index ({}, {}) out of bounds for a {}x{} view",
+                row, col, self.rows, self.cols
+            );
+        }
+        self.data[row * self.stride + col]
+    }
+
+    pub fn row(&self, row: usize) -> &'a [T] {
+        if row >= self.rows {
+            panic!(
+                "Row {} out of bounds for a {}x{} view",
+                row, self.rows, self.cols
+            );
+        }
+        // Views without columns keep no data, whatever their number of rows
+        if self.cols == 0 {
+            return &[];
+        }
+        &self.data[row * self.stride..row * self.stride + self.cols]
+    }
+
+    pub fn column(&self, col: usize) -> MatrixView<'a, T> {
+        self.slice(0..self.rows, col..col + 1)
+    }
+
+    pub fn slice(&self, rows: Range<usize>, cols: Range<usize>) -> MatrixView<'a, T> {
+        if rows.start > rows.end
+            || rows.end > self.rows
+            || cols.start > cols.end
+            || cols.end > self.cols
+        {
+            panic!(
+                "Slice {:?} x {:?} out of bounds for a {}x{} view",
+                rows, cols, self.rows, self.cols
+            );
+        }
+
+        let (height, width) = (rows.end - rows.start, cols.end - cols.start);
+        let data = if height == 0 || width == 0 {
+            &self.data[..0]
+        } else {
+            let start = rows.start * self.stride + cols.start;
+            &self.data[start..start + (height - 1) * self.stride + width]
+        };
+
+        MatrixView {
+            rows: height,
+            cols: width,
+            stride: self.stride,
+            data,
+        }
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &'a [T]> + 'a {
+        let view = *self;
+        (0..view.rows).map(move |row| view.row(row))
+    }
+
+    pub fn iter_columns(&self) -> impl Iterator<Item = MatrixView<'a, T>> + 'a {
+        let view = *self;
+        (0..view.cols).map(move |col| view.column(col))
+    }
+
+    // Every element in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'a {
+        self.iter_rows().flat_map(|row| row.iter().copied())
+    }
+
+    pub fn to_matrix(&self) -> Matrix<T> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.iter().collect(),
+        }
+    }
+}
+
+impl<T: Float> Matrix<T> {
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self[(row, col)]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: T) {
+        self[(row, col)] = value;
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        self.view().row(row)
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        if row >= self.rows {
+            panic!(
+                "Row {} out of bounds for a {}x{} matrix",
+                row, self.rows, self.cols
+            );
+        }
+        &mut self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    // Columns are not contiguous, so this returns a strided view instead of a slice
+    pub fn column(&self, col: usize) -> MatrixView<'_, T> {
+        self.view().column(col)
+    }
+
+    pub fn view(&self) -> MatrixView<'_, T> {
+        MatrixView {
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.cols,
+            data: &self.data,
+        }
+    }
+
+    pub fn slice(&self, rows: Range<usize>, cols: Range<usize>) -> MatrixView<'_, T> {
+        self.view().slice(rows, cols)
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        self.view().iter_rows()
+    }
+
+    pub fn iter_columns(&self) -> impl Iterator<Item = MatrixView<'_, T>> {
+        self.view().iter_columns()
+    }
+
+    // Reinterprets the row-major data with a new shape without copying
+    pub fn reshape(self, rows: usize, cols: usize) -> Self {
+        if rows * cols != self.data.len() {
+            panic!(
+                "Cannot reshape a {}x{} matrix into {}x{}",
+                self.rows, self.cols, rows, cols
+            );
+        }
+
+        Matrix {
+            rows,
+            cols,
+            data: self.data,
+        }
+    }
+
+    // Places the matrices side by side, they must all have the same number of rows
+    pub fn hstack(matrices: &[&Matrix<T>]) -> Self {
+        if matrices.is_empty() {
+            panic!("Cannot stack zero matrices");
+        }
+        let rows = matrices[0].rows;
+        if matrices.iter().any(|matrix| matrix.rows != rows) {
+            panic!("Rows do not match between the matrices");
+        }
+
+        let cols = matrices.iter().map(|matrix| matrix.cols).sum();
+        let mut buffer: Vec<T> = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            for matrix in matrices {
+                buffer.extend_from_slice(matrix.row(row));
+            }
+        }
+
+        Matrix {
+            rows,
+            cols,
+            data: buffer,
+        }
+    }
+
+    // Places the matrices on top of each other, they must all have the same number of columns
+    pub fn vstack(matrices: &[&Matrix<T>]) -> Self {
+        if matrices.is_empty() {
+            panic!("Cannot stack zero matrices");
+        }
+        let cols = matrices[0].cols;
+        if matrices.iter().any(|matrix| matrix.cols != cols) {
+            panic!("Columns do not match between the matrices");
+        }
+
+        Matrix {
+            rows: matrices.iter().map(|matrix| matrix.rows).sum(),
+            cols,
+            data: matrices
+                .iter()
+                .flat_map(|matrix| matrix.data.iter().copied())
+                .collect(),
+        }
+    }
+}
+
+impl<'a, T: Float> From<MatrixView<'a, T>> for Matrix<T> {
+    fn from(view: MatrixView<'a, T>) -> Self {
+        view.to_matrix()
+    }
+}
+
+#[test]
+fn access() {
+    let mut matrix = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3);
+
+    assert_eq!(matrix.get(1, 2), 6.0);
+    assert_eq!(matrix.row(1), &[4.0, 5.0, 6.0]);
+    assert_eq!(
+        matrix.column(1).iter().collect::<Vec<f64>>(),
+        vec![2.0, 5.0]
+    );
+
+    matrix.set(0, 1, -2.0);
+    matrix.row_mut(1)[0] = -4.0;
+    assert_eq!(matrix.data, vec![1.0, -2.0, 3.0, -4.0, 5.0, 6.0]);
+
+    assert_eq!(
+        matrix.iter_rows().collect::<Vec<&[f64]>>(),
+        vec![&[1.0, -2.0, 3.0][..], &[-4.0, 5.0, 6.0][..]]
+    );
+    assert_eq!(
+        matrix
+            .iter_columns()
+            .map(|column| column.to_matrix().data)
+            .collect::<Vec<Vec<f64>>>(),
+        vec![vec![1.0, -4.0], vec![-2.0, 5.0], vec![3.0, 6.0]]
+    );
+}
+
+#[test]
+#[should_panic]
+fn get_out_of_bounds() {
+    let matrix: Matrix = Matrix::zero(2, 3);
+    matrix.get(0, 3);
+}
+
+#[test]
+fn slicing() {
+    let matrix = Matrix::from_vec(&(0..20).map(|x| x as f64).collect(), 4, 5);
+
+    let view = matrix.slice(1..4, 1..3);
+    assert_eq!((view.rows, view.cols), (3, 2));
+    assert_eq!(view.get(0, 0), 6.0);
+    assert_eq!(view.row(2), &[16.0, 17.0]);
+    assert_eq!(
+        Matrix::from(view),
+        Matrix::from_vec(&vec![6.0, 7.0, 11.0, 12.0, 16.0, 17.0], 3, 2)
+    );
+
+    // Slicing a view is relative to the view
+    let inner = view.slice(1..3, 1..2);
+    assert_eq!(inner.to_matrix().data, vec![12.0, 17.0]);
+
+    let empty = matrix.slice(4..4, 0..5);
+    assert_eq!(empty.to_matrix().data, Vec::<f64>::new());
+
+    let no_columns = matrix.slice(0..3, 2..2);
+    assert_eq!((no_columns.rows, no_columns.cols), (3, 0));
+    assert_eq!(no_columns.row(1), &[] as &[f64]);
+    assert_eq!(no_columns.iter_rows().count(), 3);
+    assert_eq!(
+        no_columns.to_matrix(),
+        Matrix::from_vec(&Vec::<f64>::new(), 3, 0)
+    );
+}
+
+#[test]
+fn reshape_and_stack() {
+    let a = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3);
+    let b = Matrix::from_vec(&vec![7.0, 8.0], 2, 1);
+
+    let reshaped = a.clone().reshape(3, 2);
+    assert_eq!((reshaped.rows, reshaped.cols), (3, 2));
+    assert_eq!(reshaped.data, a.data);
+
+    assert_eq!(
+        Matrix::hstack(&[&a, &b]).data,
+        vec![1.0, 2.0, 3.0, 7.0, 4.0, 5.0, 6.0, 8.0]
+    );
+    let stacked = Matrix::vstack(&[&a, &reshaped.reshape(2, 3)]);
+    assert_eq!((stacked.rows, stacked.cols), (4, 3));
+    assert_eq!(stacked.row(3), &[4.0, 5.0, 6.0]);
+}
+
+#[test]
+#[should_panic]
+fn hstack_mismatch() {
+    let a: Matrix = Matrix::zero(2, 3);
+    Matrix::hstack(&[&a, &Matrix::zero(3, 1)]);
+}
@@ -1,19 +1,27 @@
-use crate::neat::node_gene::NodeGene;
+use crate::{
+    float::{self, Float},
+    neat::node_gene::NodeGene,
+};
 use rand::Rng;
 
-#[derive(Clone, Debug)]
-pub struct ConnectionGene {
-    pub in_node: NodeGene,
-    pub out_node: NodeGene,
-    pub weight: f32,
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConnectionGene<T = f64> {
+    pub in_node: NodeGene<T>,
+    pub out_node: NodeGene<T>,
+    pub weight: T,
     pub enabled: bool,
     pub innovation_id: i8,
 }
 
-impl ConnectionGene {
-    pub fn new(in_node: NodeGene, out_node: NodeGene, innovation_id: i8) -> Self {
-        let weight = rand::thread_rng().gen_range(-1.0..1.0);
-        let enabled = rand::thread_rng().gen_bool(0.5);
+impl<T: Float> ConnectionGene<T> {
+    pub fn new<R: Rng + ?Sized>(
+        in_node: NodeGene<T>,
+        out_node: NodeGene<T>,
+        innovation_id: i8,
+        rng: &mut R,
+    ) -> Self {
+        let weight = float::uniform(rng, -T::one(), T::one());
+        let enabled = rng.gen_bool(0.5);
 
         return ConnectionGene {
             in_node,
@@ -2,53 +2,65 @@ use std::collections::BTreeMap;
 
 use rand::{seq::SliceRandom, Rng};
 
-use crate::neat::{
-    connection_gene::ConnectionGene,
-    innovation,
-    node_gene::{NodeGene, NodeType},
+use crate::{
+    float::Float,
+    neat::{
+        connection_gene::ConnectionGene,
+        innovation::Innovation,
+        node_gene::{NodeGene, NodeType},
+    },
 };
 
-#[derive(Clone, Debug)]
-pub struct Genome {
-    pub connection_genes: BTreeMap<i8, ConnectionGene>, // {innovation_id: ConnectionGene}
-    pub node_genes: BTreeMap<i8, NodeGene>,             // {innovation_id: NodeGene}
+#[derive(Clone, Debug, PartialEq)]
+pub struct Genome<T = f64> {
+    pub connection_genes: BTreeMap<i8, ConnectionGene<T>>, // {innovation_id: ConnectionGene}
+    pub node_genes: BTreeMap<i8, NodeGene<T>>,             // {innovation_id: NodeGene}
 }
 
 // TODO maybe add a Gene trait with specific implementations for node and connection
-impl Genome {
-    pub fn new(node_size: i8, connection_size: i8) -> Self {
-        let connection_genes = BTreeMap::<i8, ConnectionGene>::new();
-        let node_genes = BTreeMap::<i8, NodeGene>::new();
+impl<T: Float> Genome<T> {
+    pub fn new<R: Rng + ?Sized>(
+        node_size: i8,
+        connection_size: i8,
+        innovation: &mut Innovation,
+        rng: &mut R,
+    ) -> Self {
+        let connection_genes = BTreeMap::<i8, ConnectionGene<T>>::new();
+        let node_genes = BTreeMap::<i8, NodeGene<T>>::new();
         let mut genome = Genome {
             connection_genes,
             node_genes,
         };
 
         for _ in 0..node_size {
-            genome.add_node_gene();
+            genome.add_node_gene(innovation, rng);
         }
 
         for _ in 0..connection_size {
-            genome.add_connection_gene();
+            genome.add_connection_gene(innovation, rng);
         }
 
         genome
     }
 
-    pub fn add_node_gene(&mut self) {
+    pub fn add_node_gene<R: Rng + ?Sized>(&mut self, innovation: &mut Innovation, rng: &mut R) {
         // TODO All input nodes for now
-        //let node = NodeGene::new(innovation::next_node(), NodeType::HIDDEN);
-        let node = NodeGene::random(innovation::next_node());
+        //let node = NodeGene::new(innovation.next_node(), NodeType::HIDDEN);
+        let node = NodeGene::random(innovation.next_node(), rng);
         self.node_genes.insert(node.innovation_id, node);
     }
 
-    pub fn add_connection_gene(&mut self) {
+    pub fn add_connection_gene<R: Rng + ?Sized>(
+        &mut self,
+        innovation: &mut Innovation,
+        rng: &mut R,
+    ) {
         let mut in_node_innovation: i8 = 0;
         let mut out_node_innovation: i8 = 0;
         let mut in_node = None;
         let mut out_node = None;
-        // TODO attempt to gather list of NodeGenes for selecting random Node
-        //let node_genes: Result<Vec<NodeGene>, _> = self.node_genes.clone().into_values().try_into();
+        // Only nodes of this genome can be connected
+        let node_innovations = self.node_genes.keys().copied().collect::<Vec<i8>>();
 
         loop {
             println!("Starting loop");
@@ -57,8 +69,8 @@ impl Genome {
             // TODO maybe choose random node from filtered selection instead of having it
             // completely randomized? Who owns the total collection of ALL innovation
             // nodes/connections?
-            in_node_innovation = rand::thread_rng().gen_range(1..=innovation::current_node());
-            out_node_innovation = rand::thread_rng().gen_range(1..=innovation::current_node());
+            in_node_innovation = *node_innovations.choose(rng).unwrap();
+            out_node_innovation = *node_innovations.choose(rng).unwrap();
             in_node = self.node_genes.get(&in_node_innovation);
             out_node = self.node_genes.get(&out_node_innovation);
             let mut valid = true;
@@ -97,7 +109,8 @@ impl Genome {
         let connection_gene = ConnectionGene::new(
             in_node.unwrap().clone(),
             out_node.unwrap().clone(),
-            innovation::next_connection(),
+            innovation.next_connection(),
+            rng,
         );
 
         self.connection_genes
@@ -112,7 +125,7 @@ impl Genome {
         for (layer, nodes) in sorted_nodes {
         for connection in self.connection_genes.clone() {
         if connection.in_node.layer == layer {
-        let result: f32 = connection.in_node.activation * connection.weight;
+        let result: T = connection.in_node.activation * connection.weight;
         }
         }
         }
@@ -130,9 +143,26 @@ impl Genome {
 
 #[test]
 fn create_genome() {
-    let genome = Genome::new(5, 4);
-    println!("{:#?}", genome);
-
-    let genome = Genome::new(3, 3);
-    println!("{:#?}", genome);
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let build = || {
+        let mut innovation = Innovation::new();
+        let mut rng = StdRng::seed_from_u64(42);
+        let genome: Genome = Genome::new(5, 4, &mut innovation, &mut rng);
+        (genome, innovation)
+    };
+
+    let (genome, innovation) = build();
+    assert_eq!(genome.node_genes.len(), 5);
+    assert_eq!(genome.connection_genes.len(), 4);
+    assert_eq!(innovation.current_node(), 5);
+    assert_eq!(innovation.current_connection(), 4);
+    // Another genome built in between must not shift the innovation ids
+    let mut other = Innovation::new();
+    let genome32: Genome<f32> = Genome::new(3, 3, &mut other, &mut StdRng::seed_from_u64(1));
+    assert!(genome32
+        .connection_genes
+        .values()
+        .all(|gene| (-1.0..1.0).contains(&gene.weight)));
+    assert_eq!(build(), (genome, innovation));
 }
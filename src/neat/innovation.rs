@@ -1,28 +1,31 @@
-static mut LATEST_NODE: i8 = 0;
-static mut LATEST_CONNECTION: i8 = 0;
+// Tracks the innovation numbers handed out to the genomes of one population. Each population owns
+// its tracker, so genomes built from the same seed get the same ids whatever ran before.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Innovation {
+    latest_node: i8,
+    latest_connection: i8,
+}
 
-pub fn current_node() -> i8 {
-    unsafe {
-        return LATEST_NODE
+impl Innovation {
+    pub fn new() -> Self {
+        Innovation::default()
     }
-}
 
-pub fn current_connection() -> i8 {
-    unsafe {
-        return LATEST_CONNECTION
+    pub fn current_node(&self) -> i8 {
+        self.latest_node
     }
-}
 
-pub fn next_node() -> i8 {
-    unsafe {
-        LATEST_NODE += 1;
-        return LATEST_NODE
+    pub fn current_connection(&self) -> i8 {
+        self.latest_connection
+    }
+
+    pub fn next_node(&mut self) -> i8 {
+        self.latest_node += 1;
+        self.latest_node
     }
-}
 
-pub fn next_connection() -> i8 {
-    unsafe {
-        LATEST_CONNECTION += 1;
-        return LATEST_CONNECTION
+    pub fn next_connection(&mut self) -> i8 {
+        self.latest_connection += 1;
+        self.latest_connection
     }
 }
@@ -1,3 +1,4 @@
+use crate::float::{self, Float};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 
@@ -20,15 +21,15 @@ impl Distribution<NodeType> for Standard {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct NodeGene {
-    pub activation: f32,
+pub struct NodeGene<T = f64> {
+    pub activation: T,
     pub innovation_id: i8,
     pub node_type: NodeType,
 }
 
-impl NodeGene {
-    pub fn new(innovation_id: i8, node_type: NodeType) -> Self {
-        let activation: f32 = rand::thread_rng().gen_range(-1.0..1.0);
+impl<T: Float> NodeGene<T> {
+    pub fn new<R: Rng + ?Sized>(innovation_id: i8, node_type: NodeType, rng: &mut R) -> Self {
+        let activation = float::uniform(rng, -T::one(), T::one());
         return NodeGene {
             activation,
             innovation_id,
@@ -36,9 +37,9 @@ impl NodeGene {
         };
     }
 
-    pub fn random(innovation_id: i8) -> Self {
-        let activation: f32 = rand::thread_rng().gen_range(-1.0..1.0);
-        let node_type: NodeType = rand::random();
+    pub fn random<R: Rng + ?Sized>(innovation_id: i8, rng: &mut R) -> Self {
+        let activation = float::uniform(rng, -T::one(), T::one());
+        let node_type: NodeType = rng.gen();
         return NodeGene {
             activation,
             innovation_id,
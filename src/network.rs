@@ -1,93 +1,871 @@
+use std::{fs::File, path::Path};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    activation::{Activation, SIGMOID},
-    matrix::Matrix,
+    activation::{self, Activation, SIGMOID},
+    float::{self, Float},
+    matrix::{CscMatrix, Matrix},
+    preprocessing::Scaler,
     training_data::TrainingData,
 };
 
-pub struct Network {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientClip {
+    // Clamp every gradient element to [-limit, limit]
+    Value(f64),
+    // Rescale all gradients together so their global L2 norm is at most the limit
+    Norm(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DivergencePolicy {
+    Stop,
+    Rollback,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrainingError {
+    Loss { layer: usize },
+    Gradient { layer: usize },
+    Weights { layer: usize },
+}
+
+impl std::fmt::Display for TrainingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TrainingError::Loss { layer } => {
+                write!(
+                    f,
+                    "Loss is not finite, first non-finite output at layer {}",
+                    layer
+                )
+            }
+            TrainingError::Gradient { layer } => {
+                write!(f, "Gradient is not finite at layer {}", layer)
+            }
+            TrainingError::Weights { layer } => {
+                write!(f, "Weights or biases are not finite at layer {}", layer)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TrainingError {}
+
+#[derive(Debug)]
+pub enum ModelError {
+    Io(std::io::Error),
+    Format(serde_yaml::Error),
+    UnknownActivation(String),
+    ShapeMismatch { layer: usize },
+    Scaler { target: bool },
+    EmptyTrunk,
+    ActivationMismatch { saved: String, network: String },
+}
+
+impl std::fmt::Display for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ModelError::Io(error) => write!(f, "Could not access model file: {}", error),
+            ModelError::Format(error) => write!(f, "Invalid model file: {}", error),
+            ModelError::UnknownActivation(name) => write!(f, "Unknown activation '{}'", name),
+            ModelError::ShapeMismatch { layer } => {
+                write!(
+                    f,
+                    "Saved model does not match the network shape at layer {}",
+                    layer
+                )
+            }
+            ModelError::Scaler { target } => write!(
+                f,
+                "Saved {} scaler does not match the network",
+                if *target { "target" } else { "input" }
+            ),
+            ModelError::EmptyTrunk => write!(f, "Saved model has no layers besides its output"),
+            ModelError::ActivationMismatch { saved, network } => write!(
+                f,
+                "Saved model uses activation '{}' but the network uses '{}'",
+                saved, network
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+impl From<std::io::Error> for ModelError {
+    fn from(error: std::io::Error) -> Self {
+        ModelError::Io(error)
+    }
+}
+
+impl From<serde_yaml::Error> for ModelError {
+    fn from(error: serde_yaml::Error) -> Self {
+        ModelError::Format(error)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+struct SavedNetwork<T> {
+    layer_sizes: Vec<usize>,
+    weights: Vec<Matrix<T>>,
+    biases: Vec<Matrix<T>>,
+    activation: String,
+    learning_rate: T,
+    frozen: Vec<bool>,
+    // Optional so models saved before scalers existed still load
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input_scaler: Option<Scaler<T>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target_scaler: Option<Scaler<T>>,
+}
+
+impl<T: Float> SavedNetwork<T> {
+    fn validate(&self) -> Result<(), ModelError> {
+        let layers = self.layer_sizes.len().saturating_sub(1);
+        if layers == 0 || self.weights.len() != layers || self.biases.len() != layers {
+            return Err(ModelError::ShapeMismatch { layer: 0 });
+        }
+        if self.frozen.len() != layers {
+            return Err(ModelError::ShapeMismatch { layer: 0 });
+        }
+
+        for layer in 0..layers {
+            let weights = &self.weights[layer];
+            let biases = &self.biases[layer];
+            if weights.rows != self.layer_sizes[layer + 1]
+                || weights.cols != self.layer_sizes[layer]
+                || weights.data.len() != weights.rows * weights.cols
+                || biases.rows != self.layer_sizes[layer + 1]
+                || biases.cols != 1
+                || biases.data.len() != biases.rows
+            {
+                return Err(ModelError::ShapeMismatch { layer });
+            }
+        }
+
+        if let Some(scaler) = &self.input_scaler {
+            if scaler.width() != self.layer_sizes[0] || !scaler.is_consistent() {
+                return Err(ModelError::Scaler { target: false });
+            }
+        }
+        if let Some(scaler) = &self.target_scaler {
+            if scaler.width() != self.layer_sizes[layers] || !scaler.is_consistent() {
+                return Err(ModelError::Scaler { target: true });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Weights and biases of a network at a point in time
+type Checkpoint<T> = (Vec<Matrix<T>>, Vec<Matrix<T>>);
+
+// Scratch buffers reused across training steps so backpropagation does not allocate once warm
+#[derive(Debug, Default)]
+struct Workspace<T> {
+    // errors[i] is the error at layer_outputs[i]
+    errors: Vec<Matrix<T>>,
+    weight_deltas: Vec<Matrix<T>>,
+    bias_deltas: Vec<Matrix<T>>,
+    // First layer weight deltas after a sparse pass, one column per stored input
+    sparse_weight_deltas: Option<Matrix<T>>,
+}
+
+impl<T: Float> Workspace<T> {
+    fn prepare(&mut self, layer_sizes: &[usize]) {
+        let layers = layer_sizes.len() - 1;
+        let prepared = self.errors.len() == layer_sizes.len()
+            && self
+                .errors
+                .iter()
+                .zip(layer_sizes)
+                .all(|(error, size)| error.rows == *size);
+        if prepared {
+            return;
+        }
+
+        self.errors = layer_sizes
+            .iter()
+            .map(|size| Matrix::zero(*size, 1))
+            .collect();
+        self.weight_deltas = (0..layers)
+            .map(|layer| Matrix::zero(layer_sizes[layer + 1], layer_sizes[layer]))
+            .collect();
+        self.bias_deltas = (0..layers)
+            .map(|layer| Matrix::zero(layer_sizes[layer + 1], 1))
+            .collect();
+    }
+
+    // Deltas that will actually be applied; frozen layers must not count towards the global norm
+    // Weight deltas of the sparse first layer together with the input columns they belong to
+    fn sparse_deltas<'a>(
+        &'a self,
+        input: &'a Option<CscMatrix<T>>,
+        layer: usize,
+    ) -> Option<(&'a CscMatrix<T>, &'a Matrix<T>)> {
+        match (input, &self.sparse_weight_deltas) {
+            (Some(input), Some(deltas)) if layer == 0 => Some((input, deltas)),
+            _ => None,
+        }
+    }
+
+    fn trainable_deltas<'a>(
+        &'a mut self,
+        frozen: &'a [bool],
+        sparse: bool,
+    ) -> impl Iterator<Item = &'a mut Matrix<T>> + 'a {
+        let (first, rest) = self.weight_deltas.split_first_mut().unwrap();
+        let first = match &mut self.sparse_weight_deltas {
+            Some(deltas) if sparse => deltas,
+            _ => first,
+        };
+
+        std::iter::once(first)
+            .chain(rest)
+            .zip(frozen)
+            .chain(self.bias_deltas.iter_mut().zip(frozen))
+            .filter(|(_, frozen)| !**frozen)
+            .map(|(delta, _)| delta)
+    }
+}
+
+#[derive(Debug)]
+pub struct Network<T = f64> {
     layer_sizes: Vec<usize>,
-    weights: Vec<Matrix>,
-    biases: Vec<Matrix>,
-    layer_outputs: Vec<Matrix>,
-    activation: Activation,
-    learning_rate: f64,
+    weights: Vec<Matrix<T>>,
+    biases: Vec<Matrix<T>>,
+    layer_outputs: Vec<Matrix<T>>,
+    activation: Activation<T>,
+    learning_rate: T,
+    gradient_clip: Option<GradientClip>,
+    divergence_policy: DivergencePolicy,
+    frozen: Vec<bool>,
+    rng: StdRng,
+    workspace: Workspace<T>,
+    // Input of the last pass when it came through feed_forward_sparse, layer_outputs[0] is then
+    // left empty
+    sparse_input: Option<CscMatrix<T>>,
+    input_scaler: Option<Scaler<T>>,
+    target_scaler: Option<Scaler<T>>,
 }
 
-impl Network {
-    pub fn new(layer_sizes: Vec<usize>, activation: Activation, learning_rate: f64) -> Network {
-        let mut weights: Vec<Matrix> = vec![];
-        let mut biases: Vec<Matrix> = vec![];
+impl<T: Float> Network<T> {
+    pub fn new(layer_sizes: Vec<usize>, activation: Activation<T>, learning_rate: T) -> Network<T> {
+        Network::with_rng(
+            layer_sizes,
+            activation,
+            learning_rate,
+            StdRng::from_entropy(),
+        )
+    }
+
+    pub fn with_seed(
+        layer_sizes: Vec<usize>,
+        activation: Activation<T>,
+        learning_rate: T,
+        seed: u64,
+    ) -> Network<T> {
+        Network::with_rng(
+            layer_sizes,
+            activation,
+            learning_rate,
+            StdRng::seed_from_u64(seed),
+        )
+    }
+
+    // The rng is kept by the network and drives both initialization and shuffling during training
+    pub fn with_rng(
+        layer_sizes: Vec<usize>,
+        activation: Activation<T>,
+        learning_rate: T,
+        mut rng: StdRng,
+    ) -> Network<T> {
+        let mut weights: Vec<Matrix<T>> = vec![];
+        let mut biases: Vec<Matrix<T>> = vec![];
 
         for i in 0..layer_sizes.len() - 1 {
-            weights.push(Matrix::random(layer_sizes[i + 1], layer_sizes[i]));
-            biases.push(Matrix::random(layer_sizes[i + 1], 1));
+            weights.push(Matrix::random(layer_sizes[i + 1], layer_sizes[i], &mut rng));
+            biases.push(Matrix::random(layer_sizes[i + 1], 1, &mut rng));
         }
 
         Network {
+            frozen: vec![false; weights.len()],
             layer_sizes,
             weights,
             biases,
             layer_outputs: vec![],
             activation,
             learning_rate,
+            gradient_clip: None,
+            divergence_policy: DivergencePolicy::Stop,
+            rng,
+            workspace: Workspace::default(),
+            sparse_input: None,
+            input_scaler: None,
+            target_scaler: None,
+        }
+    }
+
+    pub fn layer_sizes(&self) -> &[usize] {
+        &self.layer_sizes
+    }
+
+    pub fn weights(&self) -> &[Matrix<T>] {
+        &self.weights
+    }
+
+    pub fn biases(&self) -> &[Matrix<T>] {
+        &self.biases
+    }
+
+    // Input followed by every layer's activations from the last call to feed_forward. After
+    // feed_forward_sparse the input slot is an empty 0x0 matrix, the input is never densified
+    pub fn layer_outputs(&self) -> &[Matrix<T>] {
+        &self.layer_outputs
+    }
+
+    pub fn activation(&self) -> &Activation<T> {
+        &self.activation
+    }
+
+    pub fn learning_rate(&self) -> T {
+        self.learning_rate
+    }
+
+    pub fn set_layer(&mut self, layer: usize, weights: Matrix<T>, biases: Matrix<T>) {
+        if weights.rows != self.layer_sizes[layer + 1] || weights.cols != self.layer_sizes[layer] {
+            panic!("Weights do not match the layer shape");
+        }
+        if biases.rows != self.layer_sizes[layer + 1] || biases.cols != 1 {
+            panic!("Biases do not match the layer shape");
+        }
+
+        self.weights[layer] = weights;
+        self.biases[layer] = biases;
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        self.weights
+            .iter()
+            .chain(self.biases.iter())
+            .map(|matrix| matrix.data.len())
+            .sum()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut rows = vec![[
+            "Layer".to_string(),
+            "Type".to_string(),
+            "Shape".to_string(),
+            "Activation".to_string(),
+            "Params".to_string(),
+            "Trainable".to_string(),
+        ]];
+        let mut trainable = 0;
+
+        for layer in 0..self.weights.len() {
+            let params = self.weights[layer].data.len() + self.biases[layer].data.len();
+            if !self.frozen[layer] {
+                trainable += params;
+            }
+
+            rows.push([
+                layer.to_string(),
+                "Dense".to_string(),
+                format!(
+                    "{} -> {}",
+                    self.layer_sizes[layer],
+                    self.layer_sizes[layer + 1]
+                ),
+                self.activation.name.to_string(),
+                params.to_string(),
+                if self.frozen[layer] { "no" } else { "yes" }.to_string(),
+            ]);
+        }
+
+        let mut widths = [0; 6];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+
+        let mut summary = String::new();
+        for row in &rows {
+            let cells = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<String>>();
+            summary.push_str(cells.join("  ").trim_end());
+            summary.push('\n');
+        }
+        summary.push_str(&format!("Total params: {}\n", self.parameter_count()));
+        summary.push_str(&format!("Trainable params: {}\n", trainable));
+
+        summary
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ModelError> {
+        let saved = SavedNetwork {
+            layer_sizes: self.layer_sizes.clone(),
+            weights: self.weights.clone(),
+            biases: self.biases.clone(),
+            activation: self.activation.name.to_string(),
+            learning_rate: self.learning_rate,
+            frozen: self.frozen.clone(),
+            input_scaler: self.input_scaler.clone(),
+            target_scaler: self.target_scaler.clone(),
+        };
+        serde_yaml::to_writer(File::create(path)?, &saved)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Network<T>, ModelError> {
+        let saved = Network::read_saved(path)?;
+        let activation = activation::from_name(&saved.activation)
+            .ok_or(ModelError::UnknownActivation(saved.activation))?;
+
+        Ok(Network {
+            layer_sizes: saved.layer_sizes,
+            weights: saved.weights,
+            biases: saved.biases,
+            layer_outputs: vec![],
+            activation,
+            learning_rate: saved.learning_rate,
+            gradient_clip: None,
+            divergence_policy: DivergencePolicy::Stop,
+            frozen: saved.frozen,
+            rng: StdRng::from_entropy(),
+            workspace: Workspace::default(),
+            sparse_input: None,
+            input_scaler: saved.input_scaler,
+            target_scaler: saved.target_scaler,
+        })
+    }
+
+    // Copies every layer of a saved model except its output layer into this network
+    pub fn load_trunk<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ModelError> {
+        let saved = Network::read_saved(path)?;
+        if saved.activation != self.activation.name {
+            return Err(ModelError::ActivationMismatch {
+                saved: saved.activation,
+                network: self.activation.name.to_string(),
+            });
+        }
+
+        let trunk_layers = saved.weights.len() - 1;
+        if trunk_layers == 0 {
+            return Err(ModelError::EmptyTrunk);
+        }
+        if trunk_layers >= self.weights.len() {
+            return Err(ModelError::ShapeMismatch {
+                layer: self.weights.len() - 1,
+            });
+        }
+
+        for layer in 0..trunk_layers {
+            if saved.weights[layer].rows != self.weights[layer].rows
+                || saved.weights[layer].cols != self.weights[layer].cols
+            {
+                return Err(ModelError::ShapeMismatch { layer });
+            }
+        }
+
+        self.weights[..trunk_layers].clone_from_slice(&saved.weights[..trunk_layers]);
+        self.biases[..trunk_layers].clone_from_slice(&saved.biases[..trunk_layers]);
+        // The trunk expects its inputs scaled the way they were during pretraining
+        if saved.input_scaler.is_some() {
+            self.input_scaler = saved.input_scaler;
+        }
+
+        Ok(())
+    }
+
+    fn read_saved<P: AsRef<Path>>(path: P) -> Result<SavedNetwork<T>, ModelError> {
+        let saved: SavedNetwork<T> = serde_yaml::from_reader(File::open(path)?)?;
+        saved.validate()?;
+        Ok(saved)
+    }
+
+    pub fn freeze(&mut self, layer: usize) {
+        if layer >= self.frozen.len() {
+            panic!("Layer {} does not exist", layer);
+        }
+        self.frozen[layer] = true;
+    }
+
+    pub fn unfreeze(&mut self, layer: usize) {
+        if layer >= self.frozen.len() {
+            panic!("Layer {} does not exist", layer);
+        }
+        self.frozen[layer] = false;
+    }
+
+    pub fn is_frozen(&self, layer: usize) -> bool {
+        self.frozen[layer]
+    }
+
+    // Drops the output layer and replaces it with a freshly initialized, trainable one
+    pub fn replace_head(&mut self, output_size: usize) {
+        let inputs = self.layer_sizes[self.layer_sizes.len() - 2];
+
+        self.weights.pop();
+        self.biases.pop();
+        self.frozen.pop();
+        self.layer_sizes.pop();
+
+        self.weights
+            .push(Matrix::random(output_size, inputs, &mut self.rng));
+        self.biases
+            .push(Matrix::random(output_size, 1, &mut self.rng));
+        self.frozen.push(false);
+        self.layer_sizes.push(output_size);
+        self.layer_outputs = vec![];
+        self.workspace = Workspace::default();
+        self.target_scaler = None;
+    }
+
+    pub fn set_gradient_clip(&mut self, gradient_clip: Option<GradientClip>) {
+        self.gradient_clip = gradient_clip;
+    }
+
+    pub fn set_divergence_policy(&mut self, divergence_policy: DivergencePolicy) {
+        self.divergence_policy = divergence_policy;
+    }
+
+    // Scalers fitted on the training data, saved with the model and applied by predict
+    pub fn set_input_scaler(&mut self, scaler: Option<Scaler<T>>) {
+        if let Some(scaler) = &scaler {
+            if scaler.width() != self.layer_sizes[0] {
+                panic!(
+                    "Input scaler has {} columns, the network takes {} inputs",
+                    scaler.width(),
+                    self.layer_sizes[0]
+                );
+            }
+        }
+        self.input_scaler = scaler;
+    }
+
+    pub fn set_target_scaler(&mut self, scaler: Option<Scaler<T>>) {
+        let outputs = self.layer_sizes[self.layer_sizes.len() - 1];
+        if let Some(scaler) = &scaler {
+            if scaler.width() != outputs {
+                panic!(
+                    "Target scaler has {} columns, the network has {} outputs",
+                    scaler.width(),
+                    outputs
+                );
+            }
         }
+        self.target_scaler = scaler;
+    }
+
+    pub fn input_scaler(&self) -> Option<&Scaler<T>> {
+        self.input_scaler.as_ref()
+    }
+
+    pub fn target_scaler(&self) -> Option<&Scaler<T>> {
+        self.target_scaler.as_ref()
+    }
+
+    // feed_forward on raw inputs: scales them with the input scaler and maps the outputs back to
+    // the original units with the target scaler, when those are set
+    pub fn predict(&mut self, inputs: &[T]) -> Vec<T> {
+        match &self.input_scaler {
+            Some(scaler) => self.forward(&scaler.transform(inputs)),
+            None => self.forward(inputs),
+        }
+
+        let outputs = &self.layer_outputs[self.layer_sizes.len() - 1].data;
+        match &self.target_scaler {
+            Some(scaler) => scaler.inverse_transform(outputs),
+            None => outputs.clone(),
+        }
+    }
+
+    pub fn feed_forward(&mut self, inputs: Vec<T>) -> Vec<T> {
+        self.forward(&inputs);
+        self.layer_outputs[self.layer_sizes.len() - 1].data.clone()
     }
 
-    pub fn feed_forward(&mut self, inputs: Vec<f64>) -> Vec<f64> {
+    // Writes every layer's output into layer_outputs, reusing the buffers from the previous pass
+    fn forward(&mut self, inputs: &[T]) {
         if inputs.len() != self.layer_sizes[0] {
             panic!("Invalid number of inputs");
         }
 
-        let mut output: Matrix = Matrix::from_vec(&inputs, inputs.len(), 1);
-        self.layer_outputs = vec![output.clone()];
+        let layers = self.layer_sizes.len() - 1;
+        self.layer_outputs
+            .resize_with(layers + 1, || Matrix::zero(0, 0));
+        let input = &mut self.layer_outputs[0];
+        input.rows = inputs.len();
+        input.cols = 1;
+        input.data.clear();
+        input.data.extend_from_slice(inputs);
+        self.sparse_input = None;
 
-        for layer in 0..self.layer_sizes.len() - 1 {
-            output = self.weights[layer]
-                .dot_multiply(&output)
-                .add(&self.biases[layer])
-                .map(self.activation.function);
+        self.forward_layers(0);
+    }
+
+    // Same as feed_forward, but the first layer only visits the weights of the non-zero inputs
+    pub fn feed_forward_sparse(&mut self, inputs: &CscMatrix<T>) -> Vec<T> {
+        if inputs.rows() != self.layer_sizes[0] || inputs.cols() != 1 {
+            panic!("Invalid number of inputs");
+        }
+
+        let layers = self.layer_sizes.len() - 1;
+        self.layer_outputs
+            .resize_with(layers + 1, || Matrix::zero(0, 0));
+        // Kept sparse so back_propagation only updates the weights of the non-zero inputs
+        match &mut self.sparse_input {
+            Some(input) => input.copy_from(inputs),
+            None => self.sparse_input = Some(inputs.clone()),
+        }
+        let input = &mut self.layer_outputs[0];
+        input.rows = 0;
+        input.cols = 0;
+        input.data.clear();
+
+        let output = &mut self.layer_outputs[1];
+        self.weights[0].dot_csc_into(inputs, output);
+        *output += &self.biases[0];
+        output.apply(self.activation.function);
+
+        self.forward_layers(1);
+        self.layer_outputs[layers].data.clone()
+    }
+
+    // Recomputes layer_outputs[first + 1..] from layer_outputs[first]
+    fn forward_layers(&mut self, first: usize) {
+        for layer in first..self.layer_sizes.len() - 1 {
+            let (previous, next) = self.layer_outputs.split_at_mut(layer + 1);
+            let output = &mut next[0];
+
+            self.weights[layer].dot_multiply_into(&previous[layer], output);
+            *output += &self.biases[layer];
+            output.apply(self.activation.function);
+        }
+    }
 
-            self.layer_outputs.push(output.clone());
+    pub fn back_propagation(
+        &mut self,
+        outputs: Vec<T>,
+        targets: Vec<T>,
+    ) -> Result<(), TrainingError> {
+        let layers = self.layer_sizes.len() - 1;
+        if outputs.len() != self.layer_sizes[layers] {
+            panic!("Number of outputs does not equal the number of output layer nodes");
         }
 
-        output.transpose().data
+        self.layer_outputs[layers].data.copy_from_slice(&outputs);
+        self.backward(&targets)
     }
 
-    pub fn back_propagation(&mut self, outputs: Vec<f64>, targets: Vec<f64>) {
-        if targets.len() != self.layer_sizes[self.layer_sizes.len() - 1] {
+    // Backpropagates from the outputs left in layer_outputs by the last forward pass
+    fn backward(&mut self, targets: &[T]) -> Result<(), TrainingError> {
+        let layers = self.layer_sizes.len() - 1;
+        if targets.len() != self.layer_sizes[layers] {
             panic!("Number of targets does not equal the number of output layer nodes");
         }
 
-        let output_matrix = Matrix::from_vec(&outputs, 1, outputs.len()).transpose();
-        let mut errors = Matrix::from_vec(&targets, 1, targets.len())
-            .transpose()
-            .subtract(&output_matrix);
-        let mut gradients = output_matrix.map(self.activation.derivative);
+        self.workspace.prepare(&self.layer_sizes);
+        let workspace = &mut self.workspace;
+
+        let errors = &mut workspace.errors[layers];
+        for ((error, target), output) in errors
+            .data
+            .iter_mut()
+            .zip(targets)
+            .zip(&self.layer_outputs[layers].data)
+        {
+            *error = *target - *output;
+        }
+
+        // Gather every layer's deltas before touching the weights so they can be clipped together
+        for layer in (0..layers).rev() {
+            let gradients = &mut workspace.bias_deltas[layer];
+            gradients.copy_from(&self.layer_outputs[layer + 1]);
+            gradients.apply(self.activation.derivative);
+            *gradients *= &workspace.errors[layer + 1];
+            *gradients *= self.learning_rate;
+
+            if !gradients.is_finite() {
+                return Err(TrainingError::Gradient { layer });
+            }
+
+            match &self.sparse_input {
+                Some(input) if layer == 0 => gradients.outer_csc_into(
+                    input,
+                    workspace
+                        .sparse_weight_deltas
+                        .get_or_insert_with(|| Matrix::zero(0, 0)),
+                ),
+                _ => gradients.dot_transposed_into(
+                    &self.layer_outputs[layer],
+                    &mut workspace.weight_deltas[layer],
+                ),
+            }
+            if layer > 0 {
+                let (errors, next) = workspace.errors.split_at_mut(layer + 1);
+                self.weights[layer].transposed_dot_into(&next[0], &mut errors[layer]);
+            }
+        }
+
+        self.clip_gradients();
+
+        // Check every candidate update first so a diverging step leaves all weights untouched
+        for layer in 0..layers {
+            if self.frozen[layer] {
+                continue;
+            }
 
-        for layer in (0..self.layer_sizes.len() - 1).rev() {
-            gradients = gradients.multiply(&errors).map(&|x| x * self.learning_rate);
+            let weights_finite = match self.workspace.sparse_deltas(&self.sparse_input, layer) {
+                Some((input, deltas)) => {
+                    self.weights[0].columns_sum_is_finite(input.row_indices(), deltas)
+                }
+                None => self.weights[layer].sum_is_finite(&self.workspace.weight_deltas[layer]),
+            };
+            if !weights_finite
+                || !self.biases[layer].sum_is_finite(&self.workspace.bias_deltas[layer])
+            {
+                return Err(TrainingError::Weights { layer });
+            }
+        }
 
-            self.weights[layer] = self.weights[layer]
-                .add(&gradients.dot_multiply(&self.layer_outputs[layer].transpose()));
-            self.biases[layer] = self.biases[layer].add(&gradients);
+        for layer in 0..layers {
+            if self.frozen[layer] {
+                continue;
+            }
 
-            errors = self.weights[layer].transpose().dot_multiply(&errors);
-            gradients = self.layer_outputs[layer].map(self.activation.derivative);
+            match self.workspace.sparse_deltas(&self.sparse_input, layer) {
+                Some((input, deltas)) => {
+                    self.weights[0].add_to_columns(input.row_indices(), deltas)
+                }
+                None => self.weights[layer] += &self.workspace.weight_deltas[layer],
+            }
+            self.biases[layer] += &self.workspace.bias_deltas[layer];
         }
+
+        Ok(())
     }
 
-    pub fn train(&mut self, inputs: Vec<Vec<f64>>, targets: Vec<Vec<f64>>, ephochs: u16) {
+    fn clip_gradients(&mut self) {
+        let sparse = self.sparse_input.is_some();
+        let frozen = &self.frozen;
+        let workspace = &mut self.workspace;
+
+        match self.gradient_clip {
+            None => {}
+            Some(GradientClip::Value(limit)) => {
+                let limit: T = float::cast(limit);
+                for delta in workspace.trainable_deltas(frozen, sparse) {
+                    delta.apply(|x| x.clamp(-limit, limit));
+                }
+            }
+            Some(GradientClip::Norm(max_norm)) => {
+                let norm = workspace
+                    .trainable_deltas(frozen, sparse)
+                    .map(|delta| delta.data.iter().map(|x| *x * *x).sum::<T>())
+                    .sum::<T>()
+                    .sqrt();
+                let max_norm: T = float::cast(max_norm);
+
+                if norm > max_norm {
+                    let scale = max_norm / norm;
+                    for delta in workspace.trainable_deltas(frozen, sparse) {
+                        *delta *= scale;
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_loss(&self, targets: &[T]) -> Result<(), TrainingError> {
+        let outputs = &self.layer_outputs[self.layer_sizes.len() - 1].data;
+        let loss = outputs
+            .iter()
+            .zip(targets)
+            .map(|(output, target)| (*target - *output).powi(2))
+            .sum::<T>()
+            / float::cast(outputs.len() as f64);
+
+        if loss.is_finite() {
+            return Ok(());
+        }
+
+        // layer_outputs[0] is the input, so layer_outputs[i + 1] is produced by layer i
+        let layer = self.layer_outputs[1..]
+            .iter()
+            .position(|output| !output.is_finite())
+            .unwrap_or(self.layer_sizes.len() - 2);
+
+        Err(TrainingError::Loss { layer })
+    }
+
+    pub fn train(
+        &mut self,
+        inputs: Vec<Vec<T>>,
+        targets: Vec<Vec<T>>,
+        ephochs: u16,
+    ) -> Result<(), TrainingError> {
         let mut data = TrainingData::new(&inputs, &targets);
+        let mut checkpoint = self.checkpoint();
+
         for epoch in 0..=ephochs {
             if epoch % 1000 == 0 {
                 println!("Ephoch: {}", epoch);
             }
             for i in 0..data.inputs.len() {
-                let outputs = self.feed_forward(data.inputs[i].clone());
-                self.back_propagation(outputs, data.targets[i].clone());
+                self.forward(&data.inputs[i]);
+                let result = self
+                    .check_loss(&data.targets[i])
+                    .and_then(|_| self.backward(&data.targets[i]));
+
+                if let Err(error) = result {
+                    if let Some((weights, biases)) = checkpoint {
+                        self.weights = weights;
+                        self.biases = biases;
+                    }
+                    return Err(error);
+                }
             }
-            data = data.shuffle();
+            self.update_checkpoint(&mut checkpoint);
+            data.shuffle_in_place(&mut self.rng);
         }
+
+        Ok(())
+    }
+
+    // Last known good weights and biases, only kept when divergence should roll back
+    fn checkpoint(&self) -> Option<Checkpoint<T>> {
+        match self.divergence_policy {
+            DivergencePolicy::Stop => None,
+            DivergencePolicy::Rollback => Some((self.weights.clone(), self.biases.clone())),
+        }
+    }
+
+    fn update_checkpoint(&self, checkpoint: &mut Option<Checkpoint<T>>) {
+        if let Some((weights, biases)) = checkpoint {
+            for (saved, current) in weights.iter_mut().zip(&self.weights) {
+                saved.copy_from(current);
+            }
+            for (saved, current) in biases.iter_mut().zip(&self.biases) {
+                saved.copy_from(current);
+            }
+        }
+    }
+}
+
+impl<T: Float> std::fmt::Display for Network<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.summary())
     }
 }
 
@@ -110,7 +888,7 @@ fn xor() {
     }
 
     // Test trained
-    network.train(inputs.clone(), targets, 10000);
+    network.train(inputs.clone(), targets, 10000).unwrap();
     println!("Post-Trained");
     println!(
         "input: {:#?}, result: {:#?}",
@@ -133,3 +911,461 @@ fn xor() {
         network.feed_forward(inputs[3].clone())
     );
 }
+
+#[test]
+fn gradient_clip_value() {
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 10.0);
+    network.set_gradient_clip(Some(GradientClip::Value(0.01)));
+    let weights = network.weights.clone();
+    let biases = network.biases.clone();
+
+    let outputs = network.feed_forward(vec![1.0, 0.0]);
+    network.back_propagation(outputs, vec![1.0]).unwrap();
+
+    for layer in 0..weights.len() {
+        let weight_deltas = network.weights[layer].subtract(&weights[layer]);
+        let bias_deltas = network.biases[layer].subtract(&biases[layer]);
+        for delta in weight_deltas.data.iter().chain(bias_deltas.data.iter()) {
+            assert!(delta.abs() <= 0.01 + 1e-12);
+        }
+    }
+}
+
+#[test]
+fn gradient_clip_norm() {
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 10.0);
+    network.set_gradient_clip(Some(GradientClip::Norm(0.1)));
+    let weights = network.weights.clone();
+    let biases = network.biases.clone();
+
+    let outputs = network.feed_forward(vec![1.0, 0.0]);
+    network.back_propagation(outputs, vec![1.0]).unwrap();
+
+    let mut norm = 0.0;
+    for layer in 0..weights.len() {
+        let weight_deltas = network.weights[layer].subtract(&weights[layer]);
+        let bias_deltas = network.biases[layer].subtract(&biases[layer]);
+        for delta in weight_deltas.data.iter().chain(bias_deltas.data.iter()) {
+            norm += delta * delta;
+        }
+    }
+    assert!(norm.sqrt() <= 0.1 + 1e-12);
+}
+
+#[test]
+fn divergence_stop() {
+    let inputs = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+    let targets = vec![vec![1.0], vec![1.0]];
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, f64::INFINITY);
+
+    let error = network.train(inputs, targets, 10).unwrap_err();
+
+    assert_eq!(error, TrainingError::Gradient { layer: 1 });
+}
+
+#[test]
+fn divergence_rollback() {
+    let inputs = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+    let targets = vec![vec![1.0], vec![1.0]];
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 0.5);
+    network.set_divergence_policy(DivergencePolicy::Rollback);
+    network.train(inputs.clone(), targets.clone(), 10).unwrap();
+
+    let weights = network.weights.clone();
+    let biases = network.biases.clone();
+    network.learning_rate = f64::INFINITY;
+
+    assert!(network.train(inputs, targets, 10).is_err());
+    assert_eq!(network.weights, weights);
+    assert_eq!(network.biases, biases);
+}
+
+#[test]
+fn divergence_leaves_weights_untouched() {
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 1e300);
+    // The first hidden node saturates, so only the output layer's update overflows
+    network.biases[0].data[0] = 100.0;
+    network.weights[1].data[0] = f64::MAX;
+    network.biases[1].data[0] = -f64::MAX;
+    let weights = network.weights.clone();
+    let biases = network.biases.clone();
+
+    let outputs = network.feed_forward(vec![1.0, 0.0]);
+    let error = network.back_propagation(outputs, vec![1.0]).unwrap_err();
+
+    assert_eq!(error, TrainingError::Weights { layer: 1 });
+    assert_ne!(network.workspace.weight_deltas[0], Matrix::zero(3, 2));
+    assert_eq!(network.weights, weights);
+    assert_eq!(network.biases, biases);
+}
+
+#[test]
+fn non_finite_loss() {
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 0.5);
+    network.weights[0].data[0] = f64::NAN;
+
+    network.feed_forward(vec![1.0, 0.0]);
+
+    assert_eq!(
+        network.check_loss(&[1.0]),
+        Err(TrainingError::Loss { layer: 0 })
+    );
+}
+
+#[test]
+fn seeded_training() {
+    let inputs = vec![
+        vec![1.0, 1.0],
+        vec![1.0, 0.0],
+        vec![0.0, 1.0],
+        vec![0.0, 0.0],
+    ];
+    let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
+
+    let mut a = Network::with_seed(vec![2, 3, 1], SIGMOID, 0.5, 11);
+    let mut b = Network::with_seed(vec![2, 3, 1], SIGMOID, 0.5, 11);
+    assert_eq!(a.weights, b.weights);
+    assert_eq!(a.biases, b.biases);
+
+    a.train(inputs.clone(), targets.clone(), 100).unwrap();
+    b.train(inputs.clone(), targets.clone(), 100).unwrap();
+    assert_eq!(a.weights, b.weights);
+    assert_eq!(a.biases, b.biases);
+    assert_eq!(
+        a.feed_forward(inputs[1].clone()),
+        b.feed_forward(inputs[1].clone())
+    );
+}
+
+#[test]
+fn frozen_layers() {
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 0.5);
+    network.freeze(0);
+    let weights = network.weights.clone();
+    let biases = network.biases.clone();
+
+    let outputs = network.feed_forward(vec![1.0, 0.0]);
+    network.back_propagation(outputs, vec![1.0]).unwrap();
+
+    assert!(network.is_frozen(0));
+    assert_eq!(network.weights[0], weights[0]);
+    assert_eq!(network.biases[0], biases[0]);
+    assert_ne!(network.weights[1], weights[1]);
+    assert_ne!(network.biases[1], biases[1]);
+
+    network.unfreeze(0);
+    let outputs = network.feed_forward(vec![1.0, 0.0]);
+    network.back_propagation(outputs, vec![1.0]).unwrap();
+    assert_ne!(network.weights[0], weights[0]);
+}
+
+#[test]
+fn replace_head() {
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 0.5);
+    network.freeze(0);
+    network.freeze(1);
+    let trunk = network.weights[0].clone();
+
+    network.replace_head(4);
+
+    assert_eq!(network.layer_sizes, vec![2, 3, 4]);
+    assert_eq!(network.weights[0], trunk);
+    assert_eq!((network.weights[1].rows, network.weights[1].cols), (4, 3));
+    assert_eq!((network.biases[1].rows, network.biases[1].cols), (4, 1));
+    assert!(network.is_frozen(0));
+    assert!(!network.is_frozen(1));
+    assert_eq!(network.feed_forward(vec![1.0, 0.0]).len(), 4);
+}
+
+#[test]
+fn save_and_load() {
+    let path = std::env::temp_dir().join("neuralnet_save_and_load.yaml");
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 0.25);
+    network.freeze(0);
+    network.save(&path).unwrap();
+
+    let mut loaded = Network::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.layer_sizes, network.layer_sizes);
+    assert_eq!(loaded.weights, network.weights);
+    assert_eq!(loaded.biases, network.biases);
+    assert_eq!(loaded.learning_rate, 0.25);
+    assert!(loaded.is_frozen(0));
+    assert_eq!(
+        loaded.feed_forward(vec![1.0, 0.0]),
+        network.feed_forward(vec![1.0, 0.0])
+    );
+}
+
+#[test]
+fn scalers_persist() {
+    use crate::preprocessing::Scaling;
+
+    let path = std::env::temp_dir().join("neuralnet_scalers_persist.yaml");
+    let mut data = TrainingData::new(
+        &vec![vec![0.0, 100.0], vec![10.0, 300.0]],
+        &vec![vec![-50.0], vec![50.0]],
+    );
+    let mut network = Network::with_seed(vec![2, 3, 1], SIGMOID, 0.25, 4);
+    network.set_input_scaler(Some(data.scale_inputs(Scaling::MinMax)));
+    network.set_target_scaler(Some(data.scale_targets(Scaling::MinMax)));
+
+    // predict scales the raw inputs and maps the output back from [0, 1] to [-50, 50]
+    let scaled = network.feed_forward(vec![0.5, 0.5])[0];
+    let predicted = network.predict(&[5.0, 200.0])[0];
+    assert!((predicted - (scaled * 100.0 - 50.0)).abs() < 1e-12);
+
+    network.save(&path).unwrap();
+    let mut loaded = Network::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.input_scaler(), network.input_scaler());
+    assert_eq!(loaded.target_scaler(), network.target_scaler());
+    assert_eq!(
+        loaded.predict(&[5.0, 200.0]),
+        network.predict(&[5.0, 200.0])
+    );
+
+    // Offsets and scales of different lengths are rejected on load
+    let corrupt = "scaling: MinMax\noffset: [0.0, 100.0]\nscale: [10.0]";
+    network.input_scaler = Some(serde_yaml::from_str(corrupt).unwrap());
+    network.save(&path).unwrap();
+    let corrupted = Network::<f64>::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        corrupted,
+        Err(ModelError::Scaler { target: false })
+    ));
+
+    network.replace_head(2);
+    assert!(network.target_scaler().is_none());
+}
+
+#[test]
+#[should_panic]
+fn scaler_width_mismatch() {
+    use crate::preprocessing::{Scaler, Scaling};
+
+    let mut network: Network = Network::new(vec![2, 3, 1], SIGMOID, 0.25);
+    network.set_input_scaler(Some(Scaler::fit(Scaling::Standard, &[vec![1.0]])));
+}
+
+#[test]
+fn load_trunk() {
+    let path = std::env::temp_dir().join("neuralnet_load_trunk.yaml");
+    let pretrained = Network::new(vec![2, 3, 1], SIGMOID, 0.5);
+    pretrained.save(&path).unwrap();
+
+    let mut network = Network::new(vec![2, 3, 5], SIGMOID, 0.5);
+    let head = network.weights[1].clone();
+    network.load_trunk(&path).unwrap();
+
+    let mut mismatched = Network::new(vec![4, 3, 5], SIGMOID, 0.5);
+    let result = mismatched.load_trunk(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(network.weights[0], pretrained.weights[0]);
+    assert_eq!(network.biases[0], pretrained.biases[0]);
+    assert_eq!(network.weights[1], head);
+    assert!(matches!(
+        result,
+        Err(ModelError::ShapeMismatch { layer: 0 })
+    ));
+}
+
+#[test]
+fn load_trunk_input_scaler() {
+    use crate::preprocessing::{Scaler, Scaling};
+
+    let path = std::env::temp_dir().join("neuralnet_load_trunk_input_scaler.yaml");
+    let raw = vec![vec![0.0, 100.0], vec![10.0, 300.0]];
+    let mut pretrained = Network::with_seed(vec![2, 3, 1], SIGMOID, 0.5, 5);
+    pretrained.set_input_scaler(Some(Scaler::fit(Scaling::MinMax, &raw)));
+    pretrained.save(&path).unwrap();
+
+    let mut network = Network::with_seed(vec![2, 3, 4], SIGMOID, 0.5, 6);
+    network.load_trunk(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // The fine-tuned model scales raw inputs exactly like the pretrained trunk did
+    assert_eq!(network.input_scaler(), pretrained.input_scaler());
+    pretrained.predict(&[5.0, 200.0]);
+    network.predict(&[5.0, 200.0]);
+    assert_eq!(network.layer_outputs()[1], pretrained.layer_outputs()[1]);
+}
+
+#[test]
+fn load_trunk_rejects_incompatible_models() {
+    let path = std::env::temp_dir().join("neuralnet_load_trunk_incompatible.yaml");
+    Network::new(vec![2, 1], SIGMOID, 0.5).save(&path).unwrap();
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 0.5);
+    let empty = network.load_trunk(&path);
+
+    let identity = Activation {
+        name: "identity",
+        function: |x| x,
+        derivative: |_| 1.0,
+    };
+    Network::new(vec![2, 3, 1], SIGMOID, 0.5)
+        .save(&path)
+        .unwrap();
+    let mismatched = Network::new(vec![2, 3, 1], identity, 0.5).load_trunk(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(empty, Err(ModelError::EmptyTrunk)));
+    assert!(matches!(
+        mismatched,
+        Err(ModelError::ActivationMismatch { saved, network })
+            if saved == "sigmoid" && network == "identity"
+    ));
+}
+
+#[test]
+fn summary() {
+    let mut network = Network::new(vec![2, 3, 1], SIGMOID, 0.5);
+    network.freeze(0);
+
+    let expected = "\
+Layer  Type   Shape   Activation  Params  Trainable
+0      Dense  2 -> 3  sigmoid     9       no
+1      Dense  3 -> 1  sigmoid     4       yes
+Total params: 13
+Trainable params: 4
+";
+
+    assert_eq!(network.summary(), expected);
+    assert_eq!(network.to_string(), expected);
+    assert_eq!(network.parameter_count(), 13);
+    assert_eq!(network.layer_sizes(), &[2, 3, 1]);
+    assert_eq!(network.weights()[1].rows, 1);
+    assert_eq!(network.biases()[0].rows, 3);
+    assert_eq!(network.activation().name, "sigmoid");
+}
+
+#[test]
+fn single_precision() {
+    let inputs: Vec<Vec<f32>> = vec![
+        vec![1.0, 1.0],
+        vec![1.0, 0.0],
+        vec![0.0, 1.0],
+        vec![0.0, 0.0],
+    ];
+    let targets: Vec<Vec<f32>> = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
+    let path = std::env::temp_dir().join("neuralnet_single_precision.yaml");
+
+    let mut network = Network::with_seed(vec![2, 3, 1], Activation::SIGMOID, 0.5f32, 2);
+    network.train(inputs.clone(), targets, 100).unwrap();
+    network.save(&path).unwrap();
+    let mut loaded: Network<f32> = Network::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.weights, network.weights);
+    assert_eq!(
+        loaded.feed_forward(inputs[0].clone()),
+        network.feed_forward(inputs[0].clone())
+    );
+}
+
+#[test]
+fn training_reuses_buffers() {
+    let inputs = [vec![1.0, 0.0], vec![0.0, 1.0]];
+    let targets = [vec![1.0], vec![0.0]];
+    let mut network = Network::with_seed(vec![2, 4, 3, 1], SIGMOID, 0.5, 9);
+    network.set_gradient_clip(Some(GradientClip::Norm(1.0)));
+
+    let buffers = |network: &Network| {
+        let workspace = &network.workspace;
+        network
+            .layer_outputs
+            .iter()
+            .chain(&network.weights)
+            .chain(&network.biases)
+            .chain(&workspace.errors)
+            .chain(&workspace.weight_deltas)
+            .chain(&workspace.bias_deltas)
+            .map(|matrix| matrix.data.as_ptr())
+            .collect::<Vec<*const f64>>()
+    };
+
+    // The first step allocates the buffers, after that every step must reuse them
+    network.forward(&inputs[0]);
+    network.backward(&targets[0]).unwrap();
+    let warm = buffers(&network);
+
+    for _ in 0..10 {
+        for (input, target) in inputs.iter().zip(&targets) {
+            network.forward(input);
+            network.check_loss(target).unwrap();
+            network.backward(target).unwrap();
+            assert_eq!(buffers(&network), warm);
+        }
+    }
+
+    let outputs = network.feed_forward(inputs[0].clone());
+    network
+        .back_propagation(outputs, targets[0].clone())
+        .unwrap();
+    assert_eq!(buffers(&network), warm);
+}
+
+#[test]
+fn workspace_follows_layer_sizes() {
+    let mut workspace = Workspace::<f64>::default();
+    workspace.prepare(&[2, 3, 1]);
+    workspace.prepare(&[2, 5, 1]);
+
+    let shapes = |matrices: &[Matrix]| {
+        matrices
+            .iter()
+            .map(|matrix| (matrix.rows, matrix.cols))
+            .collect::<Vec<(usize, usize)>>()
+    };
+    assert_eq!(shapes(&workspace.errors), vec![(2, 1), (5, 1), (1, 1)]);
+    assert_eq!(shapes(&workspace.weight_deltas), vec![(5, 2), (1, 5)]);
+    assert_eq!(shapes(&workspace.bias_deltas), vec![(5, 1), (1, 1)]);
+}
+
+#[test]
+fn sparse_inputs() {
+    let mut network = Network::with_seed(vec![50, 4, 2], SIGMOID, 0.5, 13);
+    let mut dense = vec![0.0; 50];
+    dense[3] = 1.0;
+    dense[41] = 2.0;
+    let sparse = CscMatrix::column_vector(50, &[(3, 1.0), (41, 2.0)]);
+
+    let expected = network.feed_forward(dense);
+    let outputs = network.feed_forward_sparse(&sparse);
+
+    for (x, y) in outputs.iter().zip(&expected) {
+        assert!((x - y).abs() < 1e-12);
+    }
+    assert_eq!(network.layer_outputs()[0], Matrix::zero(0, 0));
+    network.back_propagation(outputs, vec![1.0, 0.0]).unwrap();
+
+    // A sparse training step matches a dense one and leaves the weights of zero inputs alone
+    let mut dense_network = Network::with_seed(vec![50, 4, 2], SIGMOID, 0.5, 13);
+    let mut sparse_network = Network::with_seed(vec![50, 4, 2], SIGMOID, 0.5, 13);
+    let initial = sparse_network.weights[0].clone();
+    dense_network.set_gradient_clip(Some(GradientClip::Norm(0.1)));
+    sparse_network.set_gradient_clip(Some(GradientClip::Norm(0.1)));
+
+    let outputs = dense_network.feed_forward(sparse.to_dense().data);
+    dense_network
+        .back_propagation(outputs, vec![1.0, 0.0])
+        .unwrap();
+    let outputs = sparse_network.feed_forward_sparse(&sparse);
+    sparse_network
+        .back_propagation(outputs, vec![1.0, 0.0])
+        .unwrap();
+
+    for layer in 0..2 {
+        crate::assert_matrix_close!(sparse_network.weights[layer], dense_network.weights[layer]);
+        crate::assert_matrix_close!(sparse_network.biases[layer], dense_network.biases[layer]);
+    }
+    for row in 0..4 {
+        for col in (0..50).filter(|col| *col != 3 && *col != 41) {
+            assert_eq!(sparse_network.weights[0][(row, col)], initial[(row, col)]);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+pub mod proto;
+
+use std::{fs, path::Path};
+
+use prost::Message;
+
+use crate::{
+    float::Float,
+    matrix::Matrix,
+    network::Network,
+    preprocessing::{Scaler, Scaling},
+};
+use proto::{
+    tensor_shape_proto::{dimension, Dimension},
+    type_proto, AttributeProto, GraphProto, ModelProto, NodeProto, OperatorSetIdProto, TensorProto,
+    TensorShapeProto, TypeProto, ValueInfoProto, ATTRIBUTE_INT, TENSOR_FLOAT,
+};
+
+const IR_VERSION: i64 = 7;
+const OPSET_VERSION: i64 = 13;
+
+#[derive(Debug)]
+pub enum OnnxError {
+    Io(std::io::Error),
+    UnsupportedActivation(String),
+}
+
+impl std::fmt::Display for OnnxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OnnxError::Io(error) => write!(f, "Could not write ONNX model: {}", error),
+            OnnxError::UnsupportedActivation(name) => {
+                write!(f, "Activation '{}' has no ONNX equivalent", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OnnxError {}
+
+impl From<std::io::Error> for OnnxError {
+    fn from(error: std::io::Error) -> Self {
+        OnnxError::Io(error)
+    }
+}
+
+impl<T: Float> Network<T> {
+    // Writes the network as an ONNX graph taking a float tensor of shape [batch, inputs]. Scalers
+    // are part of the graph, so like predict it takes raw inputs and gives outputs in raw units.
+    pub fn export_onnx<P: AsRef<Path>>(&self, path: P) -> Result<(), OnnxError> {
+        fs::write(path, to_model(self)?.encode_to_vec())?;
+        Ok(())
+    }
+}
+
+fn to_model<T: Float>(network: &Network<T>) -> Result<ModelProto, OnnxError> {
+    let activation = network.activation().name;
+    let op_type = activation_op(activation)
+        .ok_or_else(|| OnnxError::UnsupportedActivation(activation.to_string()))?;
+    let layer_sizes = network.layer_sizes();
+    let layers = network.weights().len();
+
+    let mut nodes = vec![];
+    let mut initializers = vec![];
+    let mut previous = "input".to_string();
+
+    if let Some(scaler) = network.input_scaler() {
+        let scaled = "scaled_input".to_string();
+        push_scaler(
+            scaler,
+            false,
+            previous,
+            &scaled,
+            &mut nodes,
+            &mut initializers,
+        );
+        previous = scaled;
+    }
+
+    for layer in 0..layers {
+        let weights = format!("weights_{}", layer);
+        let biases = format!("biases_{}", layer);
+        let dense = format!("dense_{}", layer);
+        let output = if layer < layers - 1 {
+            format!("activation_{}", layer)
+        } else if network.target_scaler().is_some() {
+            "scaled_output".to_string()
+        } else {
+            "output".to_string()
+        };
+
+        initializers.push(tensor(&weights, &network.weights()[layer], false));
+        initializers.push(tensor(&biases, &network.biases()[layer], true));
+
+        // Gemm computes input * weights^T + biases, matching the network's column-vector layout
+        nodes.push(NodeProto {
+            input: vec![previous, weights, biases],
+            output: vec![dense.clone()],
+            name: format!("gemm_{}", layer),
+            op_type: "Gemm".to_string(),
+            attribute: vec![AttributeProto {
+                name: "transB".to_string(),
+                i: 1,
+                r#type: ATTRIBUTE_INT,
+            }],
+        });
+        nodes.push(NodeProto {
+            input: vec![dense],
+            output: vec![output.clone()],
+            name: format!("{}_{}", activation, layer),
+            op_type: op_type.to_string(),
+            attribute: vec![],
+        });
+
+        previous = output;
+    }
+
+    if let Some(scaler) = network.target_scaler() {
+        push_scaler(
+            scaler,
+            true,
+            previous,
+            "output",
+            &mut nodes,
+            &mut initializers,
+        );
+    }
+
+    Ok(ModelProto {
+        ir_version: IR_VERSION,
+        producer_name: "neuralnet".to_string(),
+        producer_version: env!("CARGO_PKG_VERSION").to_string(),
+        graph: Some(GraphProto {
+            node: nodes,
+            name: "network".to_string(),
+            initializer: initializers,
+            input: vec![value_info("input", layer_sizes[0])],
+            output: vec![value_info("output", layer_sizes[layers])],
+        }),
+        opset_import: vec![OperatorSetIdProto {
+            domain: String::new(),
+            version: OPSET_VERSION,
+        }],
+    })
+}
+
+// Appends the two nodes computing (x - offset) / scale, or x * scale + offset when inverse, with
+// ln(1 + x) and exp(x) - 1 for log scaling
+fn push_scaler<T: Float>(
+    scaler: &Scaler<T>,
+    inverse: bool,
+    input: String,
+    output: &str,
+    nodes: &mut Vec<NodeProto>,
+    initializers: &mut Vec<TensorProto>,
+) {
+    let prefix = if inverse {
+        "target_scaler"
+    } else {
+        "input_scaler"
+    };
+    let ones = vec![T::one(); scaler.width()];
+    let steps: [(&str, Option<&[T]>); 2] = match (scaler.scaling(), inverse) {
+        (Scaling::Log, false) => [("Add", Some(&ones)), ("Log", None)],
+        (Scaling::Log, true) => [("Exp", None), ("Sub", Some(&ones))],
+        (_, false) => [
+            ("Sub", Some(scaler.offset())),
+            ("Div", Some(scaler.scale())),
+        ],
+        (_, true) => [
+            ("Mul", Some(scaler.scale())),
+            ("Add", Some(scaler.offset())),
+        ],
+    };
+
+    let mut previous = input;
+    for (step, (op_type, constant)) in steps.iter().enumerate() {
+        let result = if step == steps.len() - 1 {
+            output.to_string()
+        } else {
+            format!("{}_{}", prefix, step)
+        };
+        let mut inputs = vec![previous];
+        if let Some(constant) = constant {
+            let name = format!("{}_constant_{}", prefix, step);
+            let vector = Matrix::from_vec(&constant.to_vec(), constant.len(), 1);
+            initializers.push(tensor(&name, &vector, true));
+            inputs.push(name);
+        }
+
+        nodes.push(NodeProto {
+            input: inputs,
+            output: vec![result.clone()],
+            name: format!("{}_{}", prefix, op_type.to_lowercase()),
+            op_type: op_type.to_string(),
+            attribute: vec![],
+        });
+        previous = result;
+    }
+}
+
+fn activation_op(name: &str) -> Option<&'static str> {
+    match name {
+        "sigmoid" => Some("Sigmoid"),
+        "tanh" => Some("Tanh"),
+        "relu" => Some("Relu"),
+        "softmax" => Some("Softmax"),
+        _ => None,
+    }
+}
+
+fn tensor<T: Float>(name: &str, matrix: &Matrix<T>, vector: bool) -> TensorProto {
+    let dims = if vector {
+        vec![matrix.data.len() as i64]
+    } else {
+        vec![matrix.rows as i64, matrix.cols as i64]
+    };
+
+    TensorProto {
+        dims,
+        data_type: TENSOR_FLOAT,
+        float_data: matrix.data.iter().map(|x| x.to_f32().unwrap()).collect(),
+        name: name.to_string(),
+    }
+}
+
+fn value_info(name: &str, size: usize) -> ValueInfoProto {
+    let dim = vec![
+        Dimension {
+            value: Some(dimension::Value::DimParam("batch".to_string())),
+        },
+        Dimension {
+            value: Some(dimension::Value::DimValue(size as i64)),
+        },
+    ];
+
+    ValueInfoProto {
+        name: name.to_string(),
+        r#type: Some(TypeProto {
+            value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                elem_type: TENSOR_FLOAT,
+                shape: Some(TensorShapeProto { dim }),
+            })),
+        }),
+    }
+}
+
+// Minimal evaluator for the ops the exporter emits, on a single sample
+#[cfg(test)]
+fn run_graph(graph: &GraphProto, input: &[f32]) -> Vec<f32> {
+    let mut values = std::collections::HashMap::new();
+    values.insert("input".to_string(), input.to_vec());
+    let initializer = |name: &str| graph.initializer.iter().find(|t| t.name == name).unwrap();
+
+    for node in &graph.node {
+        let x = values[&node.input[0]].clone();
+        let elementwise = |f: &dyn Fn(f32, f32) -> f32| {
+            x.iter()
+                .zip(&initializer(&node.input[1]).float_data)
+                .map(|(x, y)| f(*x, *y))
+                .collect()
+        };
+        let result = match node.op_type.as_str() {
+            "Gemm" => {
+                let weights = initializer(&node.input[1]);
+                let biases = initializer(&node.input[2]);
+                let (rows, cols) = (weights.dims[0] as usize, weights.dims[1] as usize);
+                (0..rows)
+                    .map(|row| {
+                        (0..cols)
+                            .map(|col| weights.float_data[row * cols + col] * x[col])
+                            .sum::<f32>()
+                            + biases.float_data[row]
+                    })
+                    .collect()
+            }
+            "Sigmoid" => x.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect(),
+            "Add" => elementwise(&|x, y| x + y),
+            "Sub" => elementwise(&|x, y| x - y),
+            "Mul" => elementwise(&|x, y| x * y),
+            "Div" => elementwise(&|x, y| x / y),
+            "Log" => x.iter().map(|x| x.ln()).collect(),
+            "Exp" => x.iter().map(|x| x.exp()).collect(),
+            op => panic!("Unexpected op {}", op),
+        };
+        values.insert(node.output[0].clone(), result);
+    }
+
+    values["output"].clone()
+}
+
+#[test]
+fn export_round_trip() {
+    use crate::activation::SIGMOID;
+
+    let path = std::env::temp_dir().join("neuralnet_export_round_trip.onnx");
+    let mut network = Network::with_seed(vec![2, 3, 1], SIGMOID, 0.5, 5);
+    network.export_onnx(&path).unwrap();
+
+    let model = ModelProto::decode(fs::read(&path).unwrap().as_slice()).unwrap();
+    fs::remove_file(&path).unwrap();
+    let graph = model.graph.as_ref().unwrap();
+
+    assert_eq!(model.ir_version, IR_VERSION);
+    assert_eq!(model.opset_import[0].version, OPSET_VERSION);
+    assert_eq!(
+        graph
+            .node
+            .iter()
+            .map(|node| node.op_type.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["Gemm", "Sigmoid", "Gemm", "Sigmoid"]
+    );
+    assert_eq!(
+        graph.node[2].input,
+        vec!["activation_0", "weights_1", "biases_1"]
+    );
+    assert_eq!(graph.node[0].attribute[0].name, "transB");
+    assert_eq!(graph.input[0].name, "input");
+    assert_eq!(graph.output[0].name, "output");
+    assert_eq!(graph.initializer[0].dims, vec![3, 2]);
+    assert_eq!(graph.initializer[1].dims, vec![3]);
+    assert_eq!(graph.initializer[2].dims, vec![1, 3]);
+    assert_eq!(
+        graph.initializer[0].float_data,
+        network.weights()[0]
+            .data
+            .iter()
+            .map(|x| *x as f32)
+            .collect::<Vec<f32>>()
+    );
+
+    let expected = network.feed_forward(vec![1.0, 0.0]);
+    let result = run_graph(graph, &[1.0, 0.0]);
+    assert!((result[0] as f64 - expected[0]).abs() < 1e-5);
+}
+
+#[test]
+fn export_scalers() {
+    use crate::activation::SIGMOID;
+
+    let path = std::env::temp_dir().join("neuralnet_export_scalers.onnx");
+    let rows = vec![vec![1.0, 20.0], vec![3.0, 60.0], vec![2.0, 10.0]];
+    let mut network = Network::with_seed(vec![2, 3, 1], SIGMOID, 0.5, 6);
+
+    for (input, target) in [
+        (Scaling::Standard, Scaling::MinMax),
+        (Scaling::Log, Scaling::Log),
+    ] {
+        network.set_input_scaler(Some(Scaler::fit(input, &rows)));
+        network.set_target_scaler(Some(Scaler::fit(target, &[vec![5.0], vec![15.0]])));
+        network.export_onnx(&path).unwrap();
+
+        let model = ModelProto::decode(fs::read(&path).unwrap().as_slice()).unwrap();
+        let graph = model.graph.as_ref().unwrap();
+        assert_eq!(graph.node.len(), 8);
+        assert_eq!(graph.node[2].input[0], "scaled_input");
+        assert_eq!(graph.node[5].output[0], "scaled_output");
+
+        let expected = network.predict(&[2.5, 30.0]);
+        let result = run_graph(graph, &[2.5, 30.0]);
+        assert!((result[0] as f64 - expected[0]).abs() < 1e-4 * expected[0].abs().max(1.0));
+    }
+    fs::remove_file(&path).unwrap();
+}
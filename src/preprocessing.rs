@@ -0,0 +1,242 @@
+mod encoding;
+
+pub use encoding::{
+    encode_rows, ColumnEncoding, EncodingError, LabelEncoder, OneHotEncoder, OrdinalEncoder,
+    UnknownCategory,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    float::{self, Float},
+    training_data::TrainingData,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Scaling {
+    // (x - min) / (max - min), into [0, 1] on the fitted data
+    MinMax,
+    // (x - mean) / std
+    Standard,
+    // (x - median) / interquartile range, which outliers barely move
+    Robust,
+    // ln(1 + x), for skewed non-negative values
+    Log,
+}
+
+// Per-column scaling fitted on a set of rows, each transformed as (x - offset) / scale. Columns
+// that are constant in the fitted data get a scale of 1 so they do not divide by zero.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct Scaler<T = f64> {
+    scaling: Scaling,
+    offset: Vec<T>,
+    scale: Vec<T>,
+}
+
+impl<T: Float> Scaler<T> {
+    pub fn fit(scaling: Scaling, rows: &[Vec<T>]) -> Self {
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            panic!("Every row needs the same number of columns to fit a scaler");
+        }
+        if rows.iter().flatten().any(|x| !x.is_finite()) {
+            panic!("Cannot fit a scaler on non-finite values");
+        }
+
+        let mut offset = Vec::with_capacity(width);
+        let mut scale = Vec::with_capacity(width);
+        for column in 0..width {
+            let mut values = rows.iter().map(|row| row[column]).collect::<Vec<T>>();
+            let (column_offset, column_scale) = match scaling {
+                Scaling::MinMax => {
+                    let min = values.iter().fold(T::infinity(), |min, x| min.min(*x));
+                    let max = values.iter().fold(T::neg_infinity(), |max, x| max.max(*x));
+                    (min, max - min)
+                }
+                Scaling::Standard => {
+                    let count: T = float::cast(values.len() as f64);
+                    let mean = values.iter().copied().sum::<T>() / count;
+                    let variance = values.iter().map(|x| (*x - mean).powi(2)).sum::<T>() / count;
+                    (mean, variance.sqrt())
+                }
+                Scaling::Robust => {
+                    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    let median = quantile(&values, 0.5);
+                    (median, quantile(&values, 0.75) - quantile(&values, 0.25))
+                }
+                Scaling::Log => (T::zero(), T::one()),
+            };
+
+            offset.push(column_offset);
+            scale.push(if column_scale > T::zero() {
+                column_scale
+            } else {
+                T::one()
+            });
+        }
+
+        Scaler {
+            scaling,
+            offset,
+            scale,
+        }
+    }
+
+    pub fn scaling(&self) -> Scaling {
+        self.scaling
+    }
+
+    // Number of columns the scaler was fitted on
+    pub fn width(&self) -> usize {
+        self.offset.len()
+    }
+
+    pub(crate) fn offset(&self) -> &[T] {
+        &self.offset
+    }
+
+    pub(crate) fn scale(&self) -> &[T] {
+        &self.scale
+    }
+
+    // Deserialized scalers may come with offsets and scales of different lengths
+    pub(crate) fn is_consistent(&self) -> bool {
+        self.scale.len() == self.offset.len()
+    }
+
+    pub fn transform(&self, row: &[T]) -> Vec<T> {
+        self.check_width(row);
+        match self.scaling {
+            Scaling::Log => row.iter().map(|x| x.ln_1p()).collect(),
+            _ => (0..row.len())
+                .map(|i| (row[i] - self.offset[i]) / self.scale[i])
+                .collect(),
+        }
+    }
+
+    // Maps transformed values, e.g. a network's predictions, back to the original units
+    pub fn inverse_transform(&self, row: &[T]) -> Vec<T> {
+        self.check_width(row);
+        match self.scaling {
+            Scaling::Log => row.iter().map(|x| x.exp_m1()).collect(),
+            _ => (0..row.len())
+                .map(|i| row[i] * self.scale[i] + self.offset[i])
+                .collect(),
+        }
+    }
+
+    pub fn transform_rows(&self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        rows.iter().map(|row| self.transform(row)).collect()
+    }
+
+    pub fn inverse_transform_rows(&self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        rows.iter().map(|row| self.inverse_transform(row)).collect()
+    }
+
+    fn check_width(&self, row: &[T]) {
+        if row.len() != self.width() {
+            panic!(
+                "Scaler was fitted on {} columns, got {}",
+                self.width(),
+                row.len()
+            );
+        }
+    }
+}
+
+impl<T: Float> TrainingData<T> {
+    // Fits a scaler on the inputs and scales them with it, keep the scaler to scale new inputs
+    pub fn scale_inputs(&mut self, scaling: Scaling) -> Scaler<T> {
+        let scaler = Scaler::fit(scaling, &self.inputs);
+        self.inputs = scaler.transform_rows(&self.inputs);
+        scaler
+    }
+
+    // Same for the targets, predictions then need scaler.inverse_transform
+    pub fn scale_targets(&mut self, scaling: Scaling) -> Scaler<T> {
+        let scaler = Scaler::fit(scaling, &self.targets);
+        self.targets = scaler.transform_rows(&self.targets);
+        scaler
+    }
+}
+
+// Linear interpolation between the closest ranks of sorted values
+fn quantile<T: Float>(sorted: &[T], q: f64) -> T {
+    if sorted.is_empty() {
+        return T::zero();
+    }
+    let position = q * (sorted.len() - 1) as f64;
+    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+    let fraction: T = float::cast(position - low as f64);
+    sorted[low] + (sorted[high] - sorted[low]) * fraction
+}
+
+#[test]
+fn scalings() {
+    let rows = vec![
+        vec![1.0, 10.0, 5.0],
+        vec![2.0, 20.0, 5.0],
+        vec![3.0, 30.0, 5.0],
+        vec![4.0, 1000.0, 5.0],
+    ];
+
+    let min_max = Scaler::fit(Scaling::MinMax, &rows);
+    assert_eq!(min_max.transform(&rows[0]), vec![0.0, 0.0, 0.0]);
+    assert_eq!(min_max.transform(&rows[3]), vec![1.0, 1.0, 0.0]);
+
+    let standard = Scaler::fit(Scaling::Standard, &rows);
+    let scaled = standard.transform_rows(&rows);
+    let mean = scaled.iter().map(|row| row[0]).sum::<f64>() / 4.0;
+    let variance = scaled.iter().map(|row| row[0] * row[0]).sum::<f64>() / 4.0;
+    assert!(mean.abs() < 1e-12 && (variance - 1.0).abs() < 1e-12);
+
+    // The median of the second column is 25 and its interquartile range 272.5 - 17.5 = 255
+    let robust = Scaler::fit(Scaling::Robust, &rows);
+    assert_eq!(robust.transform(&[2.5, 280.0, 5.0]), vec![0.0, 1.0, 0.0]);
+
+    let log = Scaler::fit(Scaling::Log, &rows);
+    assert_eq!(log.transform(&[0.0, 1.0, 5.0])[..2], [0.0, 2.0f64.ln()]);
+
+    for scaler in [min_max, standard, robust, log] {
+        let restored = scaler.inverse_transform_rows(&scaler.transform_rows(&rows));
+        for (restored, row) in restored.iter().flatten().zip(rows.iter().flatten()) {
+            assert!((restored - row).abs() < 1e-9 * row.abs().max(1.0));
+        }
+    }
+}
+
+#[test]
+fn scale_training_data() {
+    let mut data = TrainingData::new(
+        &vec![vec![0.0, 4.0], vec![10.0, 8.0]],
+        &vec![vec![100.0], vec![300.0]],
+    );
+
+    let inputs = data.scale_inputs(Scaling::MinMax);
+    let targets = data.scale_targets(Scaling::Standard);
+
+    assert_eq!(data.inputs, vec![vec![0.0, 0.0], vec![1.0, 1.0]]);
+    assert_eq!(data.targets, vec![vec![-1.0], vec![1.0]]);
+    assert_eq!(inputs.transform(&[5.0, 6.0]), vec![0.5, 0.5]);
+    assert_eq!(targets.inverse_transform(&[0.5]), vec![250.0]);
+}
+
+#[test]
+#[should_panic]
+fn fit_with_nan() {
+    Scaler::fit(Scaling::Robust, &[vec![1.0], vec![f64::NAN], vec![2.0]]);
+}
+
+#[test]
+#[should_panic]
+fn fit_with_infinity() {
+    Scaler::fit(Scaling::Standard, &[vec![1.0], vec![f64::INFINITY]]);
+}
+
+#[test]
+#[should_panic]
+fn scaler_width_mismatch() {
+    let scaler = Scaler::fit(Scaling::MinMax, &[vec![1.0, 2.0]]);
+    scaler.transform(&[1.0]);
+}
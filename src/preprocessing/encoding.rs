@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+
+use crate::float::{self, Float};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum UnknownCategory {
+    Error,
+    // Encoded as all zeros
+    Ignore,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EncodingError {
+    UnknownCategory(String),
+    NotANumber(String),
+    ColumnCount { expected: usize, found: usize },
+}
+
+impl std::fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EncodingError::UnknownCategory(value) => write!(f, "Unknown category '{}'", value),
+            EncodingError::NotANumber(value) => write!(f, "Cannot parse '{}' as a number", value),
+            EncodingError::ColumnCount { expected, found } => {
+                write!(f, "Expected {} columns, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+// Maps each distinct value to its index in sorted order, e.g. class names to class numbers
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LabelEncoder {
+    classes: Vec<String>,
+}
+
+impl LabelEncoder {
+    pub fn fit<S: AsRef<str>>(values: &[S]) -> Self {
+        LabelEncoder {
+            classes: distinct(values),
+        }
+    }
+
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    pub fn encode(&self, value: &str) -> Result<usize, EncodingError> {
+        self.classes
+            .binary_search_by(|class| class.as_str().cmp(value))
+            .map_err(|_| EncodingError::UnknownCategory(value.to_string()))
+    }
+
+    pub fn decode(&self, label: usize) -> Option<&str> {
+        self.classes.get(label).map(String::as_str)
+    }
+}
+
+// One column per distinct value in sorted order, set to 1 for the value and 0 elsewhere
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OneHotEncoder {
+    categories: Vec<String>,
+    unknown: UnknownCategory,
+}
+
+impl OneHotEncoder {
+    pub fn fit<S: AsRef<str>>(values: &[S], unknown: UnknownCategory) -> Self {
+        OneHotEncoder {
+            categories: distinct(values),
+            unknown,
+        }
+    }
+
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    pub fn width(&self) -> usize {
+        self.categories.len()
+    }
+
+    pub fn encode<T: Float>(&self, value: &str) -> Result<Vec<T>, EncodingError> {
+        let mut encoded = vec![T::zero(); self.width()];
+        match self
+            .categories
+            .binary_search_by(|category| category.as_str().cmp(value))
+        {
+            Ok(index) => encoded[index] = T::one(),
+            Err(_) if self.unknown == UnknownCategory::Ignore => {}
+            Err(_) => return Err(EncodingError::UnknownCategory(value.to_string())),
+        }
+        Ok(encoded)
+    }
+
+    // The category with the largest output, so softmax or sigmoid outputs decode to a class
+    pub fn decode<T: Float>(&self, outputs: &[T]) -> &str {
+        if outputs.len() != self.width() || outputs.is_empty() {
+            panic!(
+                "Expected {} outputs to decode, got {}",
+                self.width(),
+                outputs.len()
+            );
+        }
+
+        let mut best = 0;
+        for i in 1..outputs.len() {
+            if outputs[i] > outputs[best] {
+                best = i;
+            }
+        }
+        &self.categories[best]
+    }
+}
+
+// Maps values to their rank in a given order, e.g. ["low", "medium", "high"] to 0, 1 and 2
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrdinalEncoder {
+    categories: Vec<String>,
+}
+
+impl OrdinalEncoder {
+    pub fn new<S: AsRef<str>>(order: &[S]) -> Self {
+        let categories = order
+            .iter()
+            .map(|value| value.as_ref().to_string())
+            .collect::<Vec<String>>();
+        if categories.is_empty() || distinct(&categories).len() != categories.len() {
+            panic!("Ordinal categories must be non-empty and distinct");
+        }
+        OrdinalEncoder { categories }
+    }
+
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    pub fn encode<T: Float>(&self, value: &str) -> Result<T, EncodingError> {
+        self.categories
+            .iter()
+            .position(|category| category == value)
+            .map(|rank| float::cast(rank as f64))
+            .ok_or_else(|| EncodingError::UnknownCategory(value.to_string()))
+    }
+
+    // Rounds to the nearest rank, so a regression output decodes as well
+    pub fn decode<T: Float>(&self, value: T) -> &str {
+        let last = self.categories.len() - 1;
+        let rank = value.round().max(T::zero()).to_usize().unwrap_or(last);
+        &self.categories[rank.min(last)]
+    }
+}
+
+// How encode_rows turns each string column into numbers
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ColumnEncoding {
+    // Parsed as a number
+    Numeric,
+    Label(LabelEncoder),
+    OneHot(OneHotEncoder),
+    Ordinal(OrdinalEncoder),
+}
+
+impl ColumnEncoding {
+    // Number of values the column expands to
+    pub fn width(&self) -> usize {
+        match self {
+            ColumnEncoding::OneHot(encoder) => encoder.width(),
+            _ => 1,
+        }
+    }
+
+    fn encode_into<T: Float>(&self, cell: &str, row: &mut Vec<T>) -> Result<(), EncodingError> {
+        match self {
+            ColumnEncoding::Numeric => row.push(
+                T::from_str_radix(cell.trim(), 10)
+                    .map_err(|_| EncodingError::NotANumber(cell.to_string()))?,
+            ),
+            ColumnEncoding::Label(encoder) => row.push(float::cast(encoder.encode(cell)? as f64)),
+            ColumnEncoding::OneHot(encoder) => row.extend(encoder.encode::<T>(cell)?),
+            ColumnEncoding::Ordinal(encoder) => row.push(encoder.encode(cell)?),
+        }
+        Ok(())
+    }
+}
+
+// Encodes every cell with its column's encoding and concatenates the results, giving numeric rows
+// ready for TrainingData::new
+pub fn encode_rows<T: Float, S: AsRef<str>>(
+    rows: &[Vec<S>],
+    columns: &[ColumnEncoding],
+) -> Result<Vec<Vec<T>>, EncodingError> {
+    let width = columns.iter().map(ColumnEncoding::width).sum();
+
+    rows.iter()
+        .map(|row| {
+            if row.len() != columns.len() {
+                return Err(EncodingError::ColumnCount {
+                    expected: columns.len(),
+                    found: row.len(),
+                });
+            }
+
+            let mut encoded = Vec::with_capacity(width);
+            for (cell, column) in row.iter().zip(columns) {
+                column.encode_into(cell.as_ref(), &mut encoded)?;
+            }
+            Ok(encoded)
+        })
+        .collect()
+}
+
+fn distinct<S: AsRef<str>>(values: &[S]) -> Vec<String> {
+    let mut distinct = values
+        .iter()
+        .map(|value| value.as_ref().to_string())
+        .collect::<Vec<String>>();
+    distinct.sort();
+    distinct.dedup();
+    distinct
+}
+
+#[test]
+fn encoders() {
+    let colors = ["red", "green", "blue", "green"];
+
+    let labels = LabelEncoder::fit(&colors);
+    assert_eq!(labels.classes(), ["blue", "green", "red"]);
+    assert_eq!(labels.encode("red"), Ok(2));
+    assert_eq!(labels.decode(1), Some("green"));
+    assert_eq!(labels.decode(3), None);
+    assert!(labels.encode("purple").is_err());
+
+    let one_hot = OneHotEncoder::fit(&colors, UnknownCategory::Error);
+    assert_eq!(one_hot.encode::<f64>("green"), Ok(vec![0.0, 1.0, 0.0]));
+    assert_eq!(
+        one_hot.encode::<f64>("purple"),
+        Err(EncodingError::UnknownCategory("purple".to_string()))
+    );
+    assert_eq!(one_hot.decode(&[0.2, 0.1, 0.7]), "red");
+    let ignore = OneHotEncoder::fit(&colors, UnknownCategory::Ignore);
+    assert_eq!(ignore.encode::<f32>("purple"), Ok(vec![0.0; 3]));
+
+    let sizes = OrdinalEncoder::new(&["small", "medium", "large"]);
+    assert_eq!(sizes.encode::<f64>("large"), Ok(2.0));
+    assert_eq!(sizes.decode(0.8), "medium");
+    assert_eq!(sizes.decode(-3.0), "small");
+    assert_eq!(sizes.decode(9.0), "large");
+}
+
+#[test]
+fn encode_mixed_rows() {
+    let rows = vec![vec!["1.5", "red", "small"], vec!["-2", "blue", "large"]];
+    let columns = [
+        ColumnEncoding::Numeric,
+        ColumnEncoding::OneHot(OneHotEncoder::fit(&["red", "blue"], UnknownCategory::Error)),
+        ColumnEncoding::Ordinal(OrdinalEncoder::new(&["small", "large"])),
+    ];
+
+    let encoded: Vec<Vec<f64>> = encode_rows(&rows, &columns).unwrap();
+    assert_eq!(
+        encoded,
+        vec![vec![1.5, 0.0, 1.0, 0.0], vec![-2.0, 1.0, 0.0, 1.0]]
+    );
+
+    let bad = encode_rows::<f64, _>(&[vec!["x", "red", "small"]], &columns);
+    assert_eq!(bad, Err(EncodingError::NotANumber("x".to_string())));
+    let short = encode_rows::<f64, _>(&[vec!["1"]], &columns);
+    assert_eq!(
+        short,
+        Err(EncodingError::ColumnCount {
+            expected: 3,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn decode_network_outputs() {
+    use crate::{activation::SIGMOID, network::Network, training_data::TrainingData};
+
+    let species = ["setosa", "virginica", "setosa", "virginica"];
+    let encoder = OneHotEncoder::fit(&species, UnknownCategory::Error);
+    let inputs = vec![vec![0.0], vec![1.0], vec![0.1], vec![0.9]];
+    let targets = species
+        .iter()
+        .map(|name| encoder.encode(name))
+        .collect::<Result<Vec<Vec<f64>>, EncodingError>>()
+        .unwrap();
+    let data = TrainingData::new(&inputs, &targets);
+
+    let mut network = Network::with_seed(vec![1, 4, 2], SIGMOID, 1.0, 7);
+    network
+        .train(data.inputs.clone(), data.targets.clone(), 2000)
+        .unwrap();
+
+    assert_eq!(encoder.decode(&network.feed_forward(vec![0.05])), "setosa");
+    assert_eq!(
+        encoder.decode(&network.feed_forward(vec![0.95])),
+        "virginica"
+    );
+}
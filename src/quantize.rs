@@ -0,0 +1,365 @@
+use crate::{
+    activation::Activation,
+    float::{self, Float},
+    matrix::Matrix,
+    network::Network,
+    preprocessing::Scaler,
+    training_data::predicted_class,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Granularity {
+    PerLayer,
+    // One scale and zero point per output node
+    PerChannel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantizationReport {
+    pub max_abs_error: f64,
+    pub mean_abs_error: f64,
+    // Fraction of samples where both models predict the same class
+    pub agreement: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct QuantizationParams {
+    scale: f64,
+    zero_point: i8,
+}
+
+impl QuantizationParams {
+    // Affine mapping of [min, max] onto [-128, 127], always keeping 0.0 exactly representable
+    fn from_range(min: f64, max: f64) -> Self {
+        let min = min.min(0.0);
+        let max = max.max(0.0);
+        let scale = if max > min { (max - min) / 255.0 } else { 1.0 };
+        let zero_point = (-128.0 - min / scale).round().clamp(-128.0, 127.0) as i8;
+
+        QuantizationParams { scale, zero_point }
+    }
+
+    fn quantize(&self, x: f64) -> i8 {
+        ((x / self.scale).round() + self.zero_point as f64).clamp(-128.0, 127.0) as i8
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct QuantizedLayer {
+    rows: usize,
+    cols: usize,
+    weights: Vec<i8>,
+    // Either a single entry or one per row, depending on the granularity
+    weight_params: Vec<QuantizationParams>,
+    input_params: QuantizationParams,
+    // Biases are stored in the accumulator's scale so they can be added in i32
+    biases: Vec<i32>,
+}
+
+impl QuantizedLayer {
+    fn new<T: Float>(
+        weights: &Matrix<T>,
+        biases: &Matrix<T>,
+        input_params: QuantizationParams,
+        granularity: Granularity,
+    ) -> Self {
+        let to_f64 = |matrix: &Matrix<T>| {
+            matrix
+                .data
+                .iter()
+                .map(|x| x.to_f64().unwrap())
+                .collect::<Vec<f64>>()
+        };
+        let (rows, cols) = (weights.rows, weights.cols);
+        let (weights, biases) = (to_f64(weights), to_f64(biases));
+
+        let range = |values: &[f64]| {
+            let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            QuantizationParams::from_range(min, max)
+        };
+
+        let weight_params = match granularity {
+            Granularity::PerLayer => vec![range(&weights)],
+            Granularity::PerChannel => weights.chunks(cols).map(range).collect(),
+        };
+
+        let mut quantized_weights = Vec::with_capacity(weights.len());
+        let mut quantized_biases = Vec::with_capacity(rows);
+        for row in 0..rows {
+            let params = weight_params[row.min(weight_params.len() - 1)];
+            for col in 0..cols {
+                quantized_weights.push(params.quantize(weights[row * cols + col]));
+            }
+            let bias_scale = params.scale * input_params.scale;
+            quantized_biases.push((biases[row] / bias_scale).round() as i32);
+        }
+
+        QuantizedLayer {
+            rows,
+            cols,
+            weights: quantized_weights,
+            weight_params,
+            input_params,
+            biases: quantized_biases,
+        }
+    }
+
+    fn feed_forward(&self, inputs: &[f64]) -> Vec<f64> {
+        let input_zero_point = self.input_params.zero_point as i32;
+        let inputs = inputs
+            .iter()
+            .map(|x| self.input_params.quantize(*x) as i32 - input_zero_point)
+            .collect::<Vec<i32>>();
+
+        let mut outputs = Vec::with_capacity(self.rows);
+        for row in 0..self.rows {
+            let params = self.weight_params[row.min(self.weight_params.len() - 1)];
+            let weight_zero_point = params.zero_point as i32;
+            let weights = &self.weights[row * self.cols..(row + 1) * self.cols];
+
+            let mut accumulator = self.biases[row];
+            for (weight, input) in weights.iter().zip(&inputs) {
+                accumulator += (*weight as i32 - weight_zero_point) * input;
+            }
+
+            outputs.push(accumulator as f64 * params.scale * self.input_params.scale);
+        }
+
+        outputs
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct QuantizedNetwork<T = f64> {
+    layers: Vec<QuantizedLayer>,
+    activation: Activation<T>,
+    // Copied from the network, the scalers stay in floating point
+    input_scaler: Option<Scaler<T>>,
+    target_scaler: Option<Scaler<T>>,
+}
+
+impl<T: Float> QuantizedNetwork<T> {
+    pub fn feed_forward(&self, inputs: Vec<T>) -> Vec<T> {
+        if inputs.len() != self.layers[0].cols {
+            panic!("Invalid number of inputs");
+        }
+
+        let mut outputs = inputs;
+        for layer in &self.layers {
+            let inputs = outputs
+                .iter()
+                .map(|x| x.to_f64().unwrap())
+                .collect::<Vec<f64>>();
+            outputs = layer
+                .feed_forward(&inputs)
+                .into_iter()
+                .map(|x| (self.activation.function)(float::cast(x)))
+                .collect();
+        }
+
+        outputs
+    }
+
+    // Same as Network::predict, scaling raw inputs and mapping the outputs back to raw units
+    pub fn predict(&self, inputs: &[T]) -> Vec<T> {
+        let outputs = match &self.input_scaler {
+            Some(scaler) => self.feed_forward(scaler.transform(inputs)),
+            None => self.feed_forward(inputs.to_vec()),
+        };
+
+        match &self.target_scaler {
+            Some(scaler) => scaler.inverse_transform(&outputs),
+            None => outputs,
+        }
+    }
+
+    pub fn input_scaler(&self) -> Option<&Scaler<T>> {
+        self.input_scaler.as_ref()
+    }
+
+    pub fn target_scaler(&self) -> Option<&Scaler<T>> {
+        self.target_scaler.as_ref()
+    }
+
+    // Size of the quantized weights and biases in bytes
+    pub fn parameter_bytes(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.weights.len() + layer.biases.len() * std::mem::size_of::<i32>())
+            .sum()
+    }
+
+    pub fn compare(&self, network: &mut Network<T>, inputs: &[Vec<T>]) -> QuantizationReport {
+        if inputs.is_empty() {
+            panic!("Comparison requires at least one sample");
+        }
+
+        let mut max_abs_error: f64 = 0.0;
+        let mut total_abs_error = 0.0;
+        let mut outputs_count = 0;
+        let mut agreements = 0;
+
+        for input in inputs {
+            let expected = network.feed_forward(input.clone());
+            let result = self.feed_forward(input.clone());
+
+            for (expected, result) in expected.iter().zip(&result) {
+                let error = (*expected - *result).abs().to_f64().unwrap();
+                max_abs_error = max_abs_error.max(error);
+                total_abs_error += error;
+                outputs_count += 1;
+            }
+
+            if predicted_class(&expected) == predicted_class(&result) {
+                agreements += 1;
+            }
+        }
+
+        QuantizationReport {
+            max_abs_error,
+            mean_abs_error: total_abs_error / outputs_count as f64,
+            agreement: agreements as f64 / inputs.len() as f64,
+        }
+    }
+}
+
+impl<T: Float> Network<T> {
+    // Calibrates the input range of every layer on the given samples and converts the weights to
+    // int8. The samples go through feed_forward, so they are already scaled when the network has
+    // scalers.
+    pub fn quantize(
+        &mut self,
+        calibration: &[Vec<T>],
+        granularity: Granularity,
+    ) -> QuantizedNetwork<T> {
+        if calibration.is_empty() {
+            panic!("Calibration requires at least one sample");
+        }
+
+        let layers = self.weights().len();
+        let mut ranges = vec![(f64::INFINITY, f64::NEG_INFINITY); layers];
+
+        for input in calibration {
+            self.feed_forward(input.clone());
+            for (layer, range) in ranges.iter_mut().enumerate() {
+                for x in &self.layer_outputs()[layer].data {
+                    let x = x.to_f64().unwrap();
+                    range.0 = range.0.min(x);
+                    range.1 = range.1.max(x);
+                }
+            }
+        }
+
+        QuantizedNetwork {
+            layers: (0..layers)
+                .map(|layer| {
+                    QuantizedLayer::new(
+                        &self.weights()[layer],
+                        &self.biases()[layer],
+                        QuantizationParams::from_range(ranges[layer].0, ranges[layer].1),
+                        granularity,
+                    )
+                })
+                .collect(),
+            activation: self.activation().clone(),
+            input_scaler: self.input_scaler().cloned(),
+            target_scaler: self.target_scaler().cloned(),
+        }
+    }
+}
+
+#[test]
+fn quantization_params() {
+    let params = QuantizationParams::from_range(-1.0, 1.0);
+
+    assert_eq!(params.quantize(0.0), params.zero_point);
+    assert_eq!(params.quantize(-1.0), -128);
+    assert_eq!(params.quantize(1.0), 127);
+    assert_eq!(params.quantize(5.0), 127);
+
+    let positive = QuantizationParams::from_range(2.0, 4.0);
+    assert_eq!(positive.zero_point, -128);
+    assert_eq!(positive.quantize(4.0), 127);
+}
+
+#[test]
+fn quantized_layer() {
+    use crate::activation::SIGMOID;
+
+    let mut network = Network::new(vec![2, 2], SIGMOID, 0.5);
+    network.set_layer(
+        0,
+        Matrix::from_vec(&vec![1.0, -0.5, 0.25, 0.75], 2, 2),
+        Matrix::from_vec(&vec![0.1, -0.2], 2, 1),
+    );
+
+    let quantized = network.quantize(&[vec![1.0, -1.0], vec![-1.0, 1.0]], Granularity::PerChannel);
+    let layer = &quantized.layers[0];
+    let dense = layer.feed_forward(&[0.5, -0.5]);
+
+    assert_eq!(layer.weight_params.len(), 2);
+    assert_eq!(layer.weights[0], 127);
+    assert_eq!(layer.weights[3], 127);
+    assert!((dense[0] - 0.85).abs() < 0.02);
+    assert!((dense[1] - -0.45).abs() < 0.02);
+}
+
+#[test]
+fn quantize_network() {
+    use crate::activation::SIGMOID;
+
+    let inputs = vec![
+        vec![1.0, 1.0],
+        vec![1.0, 0.0],
+        vec![0.0, 1.0],
+        vec![0.0, 0.0],
+    ];
+    let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
+    let mut network = Network::with_seed(vec![2, 4, 1], SIGMOID, 0.5, 3);
+    network.train(inputs.clone(), targets, 2000).unwrap();
+
+    for granularity in [Granularity::PerLayer, Granularity::PerChannel] {
+        let quantized = network.quantize(&inputs, granularity);
+        let report = quantized.compare(&mut network, &inputs);
+
+        assert!(report.max_abs_error < 0.05);
+        assert!(report.mean_abs_error <= report.max_abs_error);
+        assert_eq!(report.agreement, 1.0);
+    }
+
+    let quantized = network.quantize(&inputs, Granularity::PerLayer);
+    assert_eq!(quantized.parameter_bytes(), 2 * 4 + 4 + (4 + 1) * 4);
+}
+
+#[test]
+fn quantize_scalers() {
+    use crate::{activation::SIGMOID, preprocessing::Scaling};
+
+    let raw = vec![vec![10.0, 1.0], vec![30.0, 5.0], vec![20.0, 3.0]];
+    let input_scaler = Scaler::fit(Scaling::MinMax, &raw);
+    let mut network = Network::with_seed(vec![2, 4, 1], SIGMOID, 0.5, 8);
+    network.set_input_scaler(Some(input_scaler.clone()));
+    network.set_target_scaler(Some(Scaler::fit(Scaling::MinMax, &[vec![-5.0], vec![5.0]])));
+
+    let quantized = network.quantize(&input_scaler.transform_rows(&raw), Granularity::PerChannel);
+    assert_eq!(quantized.input_scaler(), network.input_scaler());
+    assert_eq!(quantized.target_scaler(), network.target_scaler());
+
+    for input in &raw {
+        let expected = network.predict(input)[0];
+        // Outputs in [0, 1] are mapped back onto [-5, 5], so the error grows tenfold
+        assert!((quantized.predict(input)[0] - expected).abs() < 0.1);
+    }
+}
+
+#[test]
+#[should_panic]
+fn compare_empty() {
+    use crate::activation::SIGMOID;
+
+    let mut network = Network::new(vec![2, 2], SIGMOID, 0.5);
+    let quantized = network.quantize(&[vec![1.0, -1.0]], Granularity::PerChannel);
+
+    quantized.compare(&mut network, &[]);
+}
@@ -0,0 +1,310 @@
+use std::{
+    borrow::Cow,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    float::Float,
+    matrix::{kernels, Matrix},
+};
+
+// N-dimensional array. Element [i0, i1, ...] lives at data[i0 * strides[0] + i1 * strides[1] + ...],
+// so permute only reorders the strides and never moves data.
+#[derive(Clone, Debug)]
+pub struct Tensor<T = f64> {
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    data: Vec<T>,
+}
+
+impl<T: Float> Tensor<T> {
+    pub fn zeros(shape: &[usize]) -> Self {
+        Tensor::from_vec(vec![T::zero(); shape.iter().product()], shape)
+    }
+
+    // data is read in row-major order, the last axis varying fastest
+    pub fn from_vec(data: Vec<T>, shape: &[usize]) -> Self {
+        if data.len() != shape.iter().product::<usize>() {
+            panic!(
+                "{} elements do not fit a tensor of shape {:?}",
+                data.len(),
+                shape
+            );
+        }
+
+        Tensor {
+            shape: shape.to_vec(),
+            strides: row_major_strides(shape),
+            data,
+        }
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_contiguous(&self) -> bool {
+        self.strides == row_major_strides(&self.shape)
+    }
+
+    // Elements in row-major order of the current shape, whatever the strides are
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len()).map(move |i| self.data[self.linear_offset(i)])
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+
+    // Copy with row-major strides, needed after permute before the data can be used as a flat buffer
+    pub fn contiguous(&self) -> Self {
+        Tensor::from_vec(self.to_vec(), &self.shape)
+    }
+
+    pub fn reshape(self, shape: &[usize]) -> Self {
+        if shape.iter().product::<usize>() != self.len() {
+            panic!("Cannot reshape {:?} into {:?}", self.shape, shape);
+        }
+
+        let data = if self.is_contiguous() {
+            self.data
+        } else {
+            self.to_vec()
+        };
+        Tensor::from_vec(data, shape)
+    }
+
+    // Axis i of the result is axis axes[i] of self
+    pub fn permute(self, axes: &[usize]) -> Self {
+        let mut seen = vec![false; self.ndim()];
+        if axes.len() != self.ndim()
+            || axes
+                .iter()
+                .any(|axis| *axis >= seen.len() || std::mem::replace(&mut seen[*axis], true))
+        {
+            panic!(
+                "{:?} is not a permutation of the {} axes",
+                axes,
+                self.ndim()
+            );
+        }
+
+        Tensor {
+            shape: axes.iter().map(|axis| self.shape[*axis]).collect(),
+            strides: axes.iter().map(|axis| self.strides[*axis]).collect(),
+            data: self.data,
+        }
+    }
+
+    // Matrix product over the last two axes, [..., m, k] x [..., k, n] -> [..., m, n]. The leading
+    // batch axes must match, or other may be a plain [k, n] shared by every batch.
+    pub fn matmul(&self, other: &Tensor<T>) -> Self {
+        if self.ndim() < 2 || other.ndim() < 2 {
+            panic!("matmul needs at least two axes on both tensors");
+        }
+
+        let (batch, m, k) = split_batch(&self.shape);
+        let (other_batch, other_k, n) = split_batch(&other.shape);
+        let shared = other.ndim() == 2;
+        if k != other_k || (!shared && batch != other_batch) {
+            panic!(
+                "Size mismatch when multiplying tensors of shape {:?} and {:?}",
+                self.shape, other.shape
+            );
+        }
+
+        let a = self.contiguous_data();
+        let b = other.contiguous_data();
+        let batches: usize = batch.iter().product();
+        let mut result = vec![T::zero(); batches * m * n];
+
+        for i in 0..batches {
+            let b_offset = if shared { 0 } else { i * k * n };
+            kernels::gemm(
+                &a[i * m * k..(i + 1) * m * k],
+                &b[b_offset..b_offset + k * n],
+                &mut result[i * m * n..(i + 1) * m * n],
+                m,
+                k,
+                n,
+            );
+        }
+
+        let mut shape = batch.to_vec();
+        shape.extend_from_slice(&[m, n]);
+        Tensor::from_vec(result, &shape)
+    }
+
+    pub fn to_matrix(&self) -> Matrix<T> {
+        if self.ndim() != 2 {
+            panic!(
+                "Only a 2-D tensor converts to a Matrix, got shape {:?}",
+                self.shape
+            );
+        }
+        Matrix::from_vec(&self.to_vec(), self.shape[0], self.shape[1])
+    }
+
+    fn contiguous_data(&self) -> Cow<'_, [T]> {
+        if self.is_contiguous() {
+            Cow::Borrowed(&self.data)
+        } else {
+            Cow::Owned(self.to_vec())
+        }
+    }
+
+    // Storage offset of the i-th element in row-major order
+    fn linear_offset(&self, mut i: usize) -> usize {
+        let mut offset = 0;
+        for axis in (0..self.ndim()).rev() {
+            offset += (i % self.shape[axis]) * self.strides[axis];
+            i /= self.shape[axis];
+        }
+        offset
+    }
+
+    fn offset(&self, index: &[usize]) -> usize {
+        if index.len() != self.ndim() || index.iter().zip(&self.shape).any(|(i, size)| i >= size) {
+            panic!("Index {:?} out of bounds for shape {:?}", index, self.shape);
+        }
+        index
+            .iter()
+            .zip(&self.strides)
+            .map(|(i, stride)| i * stride)
+            .sum()
+    }
+}
+
+impl<T: Float> Index<&[usize]> for Tensor<T> {
+    type Output = T;
+
+    fn index(&self, index: &[usize]) -> &T {
+        &self.data[self.offset(index)]
+    }
+}
+
+impl<T: Float> IndexMut<&[usize]> for Tensor<T> {
+    fn index_mut(&mut self, index: &[usize]) -> &mut T {
+        let offset = self.offset(index);
+        &mut self.data[offset]
+    }
+}
+
+// Two tensors are equal when they hold the same elements in the same shape, regardless of strides
+impl<T: Float> PartialEq for Tensor<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape == other.shape && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Float> From<Matrix<T>> for Tensor<T> {
+    fn from(matrix: Matrix<T>) -> Self {
+        Tensor::from_vec(matrix.data, &[matrix.rows, matrix.cols])
+    }
+}
+
+fn row_major_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for axis in (0..shape.len().saturating_sub(1)).rev() {
+        strides[axis] = strides[axis + 1] * shape[axis + 1];
+    }
+    strides
+}
+
+// (batch axes, rows, cols) of a shape with at least two axes
+fn split_batch(shape: &[usize]) -> (&[usize], usize, usize) {
+    let n = shape.len();
+    (&shape[..n - 2], shape[n - 2], shape[n - 1])
+}
+
+#[test]
+fn shape_and_index() {
+    let mut tensor = Tensor::from_vec((0..24).map(|x| x as f64).collect(), &[2, 3, 4]);
+
+    assert_eq!(tensor.strides(), &[12, 4, 1]);
+    assert_eq!(tensor.len(), 24);
+    assert_eq!(tensor[&[1, 2, 3][..]], 23.0);
+    tensor[&[0, 1, 0][..]] = -1.0;
+    assert_eq!(tensor.to_vec()[4], -1.0);
+}
+
+#[test]
+fn permute_and_reshape() {
+    let tensor = Tensor::from_vec((0..24).map(|x| x as f64).collect(), &[2, 3, 4]);
+
+    let permuted = tensor.clone().permute(&[2, 0, 1]);
+    assert_eq!(permuted.shape(), &[4, 2, 3]);
+    assert!(!permuted.is_contiguous());
+    assert_eq!(permuted[&[3, 1, 2][..]], tensor[&[1, 2, 3][..]]);
+    assert_eq!(permuted.contiguous(), permuted);
+    assert!(permuted.contiguous().is_contiguous());
+
+    // Permuting back restores the original layout without touching the data
+    assert_eq!(permuted.clone().permute(&[1, 2, 0]), tensor);
+
+    let reshaped = permuted.reshape(&[8, 3]);
+    assert!(reshaped.is_contiguous());
+    assert_eq!(&reshaped.to_vec()[..4], &[0.0, 4.0, 8.0, 12.0]);
+}
+
+#[test]
+#[should_panic]
+fn invalid_permutation() {
+    Tensor::<f64>::zeros(&[2, 3, 4]).permute(&[0, 0, 1]);
+}
+
+#[test]
+fn batched_matmul() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(8);
+    let a: Vec<Matrix> = (0..3).map(|_| Matrix::random(4, 5, &mut rng)).collect();
+    let b: Vec<Matrix> = (0..3).map(|_| Matrix::random(5, 2, &mut rng)).collect();
+    let stack = |matrices: &[Matrix]| {
+        let (rows, cols) = (matrices[0].rows, matrices[0].cols);
+        let data = matrices.iter().flat_map(|m| m.data.clone()).collect();
+        Tensor::from_vec(data, &[matrices.len(), rows, cols])
+    };
+
+    let product = stack(&a).matmul(&stack(&b));
+    assert_eq!(product.shape(), &[3, 4, 2]);
+    let expected: Vec<Matrix> = a.iter().zip(&b).map(|(a, b)| a.dot_multiply(b)).collect();
+    assert_eq!(product, stack(&expected));
+
+    let shared = stack(&a).matmul(&Tensor::from(b[0].clone()));
+    let expected: Vec<Matrix> = a.iter().map(|a| a.dot_multiply(&b[0])).collect();
+    assert_eq!(shared, stack(&expected));
+
+    // A permuted operand gives the same result as its contiguous copy
+    let transposed = stack(&b)
+        .permute(&[0, 2, 1])
+        .contiguous()
+        .permute(&[0, 2, 1]);
+    assert_eq!(stack(&a).matmul(&transposed), stack(&a).matmul(&stack(&b)));
+}
+
+#[test]
+fn matrix_conversion() {
+    let matrix = Matrix::from_vec(&vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3);
+    let tensor = Tensor::from(matrix.clone());
+
+    assert_eq!(tensor.shape(), &[2, 3]);
+    assert_eq!(tensor.to_matrix(), matrix);
+    assert_eq!(tensor.permute(&[1, 0]).to_matrix(), matrix.transpose());
+}
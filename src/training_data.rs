@@ -1,12 +1,22 @@
+mod csv;
+mod idx;
+mod split;
+
+pub use self::csv::{write_predictions, Column, CsvError, CsvHeader, CsvOptions};
+pub use idx::IdxError;
+pub use split::KFold;
+
 use rand::Rng;
 
-pub struct TrainingData {
-    pub inputs: Vec<Vec<f64>>,
-    pub targets: Vec<Vec<f64>>,
+use crate::float::{self, Float};
+
+pub struct TrainingData<T = f64> {
+    pub inputs: Vec<Vec<T>>,
+    pub targets: Vec<Vec<T>>,
 }
 
-impl TrainingData {
-    pub fn new(inputs: &Vec<Vec<f64>>, targets: &Vec<Vec<f64>>) -> Self {
+impl<T: Clone> TrainingData<T> {
+    pub fn new(inputs: &Vec<Vec<T>>, targets: &Vec<Vec<T>>) -> Self {
         if inputs.len() != targets.len() {
             panic!("Inputs and target sizes do not match");
         }
@@ -16,32 +26,55 @@ impl TrainingData {
         }
     }
 
-    pub fn shuffle(&self) -> TrainingData {
-        let mut shuf_inputs = self.inputs.clone();
-        let mut shuf_targets = self.targets.clone();
-        let mut rng = rand::thread_rng();
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
 
-        for i in 0..self.inputs.len() {
-            let rng_idx = rng.gen_range(i..self.inputs.len());
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
 
-            let tmp = shuf_inputs[i].clone();
-            shuf_inputs[i] = shuf_inputs[rng_idx].clone();
-            shuf_inputs[rng_idx] = tmp.clone();
+    #[deprecated(note = "copies the data, use shuffle_in_place")]
+    pub fn shuffle<R: Rng + ?Sized>(&self, rng: &mut R) -> TrainingData<T> {
+        let mut shuffled = TrainingData {
+            inputs: self.inputs.clone(),
+            targets: self.targets.clone(),
+        };
+        shuffled.shuffle_in_place(rng);
+        shuffled
+    }
 
-            let tmp = shuf_targets[i].clone();
-            shuf_targets[i] = shuf_targets[rng_idx].clone();
-            shuf_targets[rng_idx] = tmp.clone();
+    // Same order as shuffle for the same rng, without copying the data every epoch
+    pub fn shuffle_in_place<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        for i in 0..self.inputs.len() {
+            let rng_idx = rng.gen_range(i..self.inputs.len());
+            self.inputs.swap(i, rng_idx);
+            self.targets.swap(i, rng_idx);
         }
+    }
+}
 
-        TrainingData {
-            inputs: shuf_inputs,
-            targets: shuf_targets,
+// Class of an output or target row, the index of its largest value. A single output is treated as
+// a binary classifier.
+pub(crate) fn predicted_class<T: Float>(outputs: &[T]) -> usize {
+    if outputs.len() == 1 {
+        return (outputs[0] >= float::cast(0.5)) as usize;
+    }
+
+    let mut best = 0;
+    for i in 1..outputs.len() {
+        if outputs[i] > outputs[best] {
+            best = i;
         }
     }
+    best
 }
 
 #[test]
+#[allow(deprecated)]
 fn shuffle() {
+    use rand::{rngs::StdRng, SeedableRng};
+
     let inputs = vec![
         vec![1.0, 1.0],
         vec![1.0, 0.0],
@@ -51,7 +84,7 @@ fn shuffle() {
     let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
 
     let training_data = TrainingData::new(&inputs, &targets);
-    let shuffled = training_data.shuffle();
+    let shuffled = training_data.shuffle(&mut StdRng::seed_from_u64(1));
     assert_ne!(shuffled.inputs, training_data.inputs);
     assert_ne!(shuffled.targets, training_data.targets);
     assert!(shuffled.inputs.contains(&training_data.inputs[0]));
@@ -63,3 +96,37 @@ fn shuffle() {
     assert!(shuffled.targets.contains(&training_data.targets[2]));
     assert!(shuffled.targets.contains(&training_data.targets[3]));
 }
+
+#[test]
+#[allow(deprecated)]
+fn shuffle_seeded() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let inputs = (0..10).map(|x| vec![x as f64]).collect::<Vec<Vec<f64>>>();
+    let targets = inputs.clone();
+    let training_data = TrainingData::new(&inputs, &targets);
+
+    let a = training_data.shuffle(&mut StdRng::seed_from_u64(3));
+    let b = training_data.shuffle(&mut StdRng::seed_from_u64(3));
+
+    assert_eq!(a.inputs, b.inputs);
+    assert_eq!(a.targets, b.targets);
+    assert_eq!(a.inputs, a.targets);
+}
+
+#[test]
+#[allow(deprecated)]
+fn shuffle_in_place() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let inputs = (0..10).map(|x| vec![x as f64]).collect::<Vec<Vec<f64>>>();
+    let training_data = TrainingData::new(&inputs, &inputs);
+
+    let mut in_place = TrainingData::new(&inputs, &inputs);
+    in_place.shuffle_in_place(&mut StdRng::seed_from_u64(5));
+    let copied = training_data.shuffle(&mut StdRng::seed_from_u64(5));
+
+    assert_ne!(in_place.inputs, training_data.inputs);
+    assert_eq!(in_place.inputs, copied.inputs);
+    assert_eq!(in_place.inputs, in_place.targets);
+}
@@ -0,0 +1,331 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+};
+
+use csv::{ReaderBuilder, StringRecord, Trim, WriterBuilder};
+
+use super::TrainingData;
+use crate::float::Float;
+
+// A column picked by its header name or by its zero-based position
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for Column {
+    fn from(index: usize) -> Self {
+        Column::Index(index)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Column::Name(name.to_string())
+    }
+}
+
+impl std::fmt::Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Column::Index(index) => write!(f, "{}", index),
+            Column::Name(name) => write!(f, "'{}'", name),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CsvHeader {
+    // The first row is a header when any of its cells is not a number
+    Auto,
+    Present,
+    Absent,
+}
+
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub header: CsvHeader,
+    // Empty means every column that is not a target
+    pub features: Vec<Column>,
+    // Empty leaves every target row empty, e.g. for data that is only predicted on
+    pub targets: Vec<Column>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            header: CsvHeader::Auto,
+            features: vec![],
+            targets: vec![],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CsvError {
+    Io(std::io::Error),
+    Csv(csv::Error),
+    UnknownColumn(Column),
+    Parse {
+        line: u64,
+        column: Column,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CsvError::Io(error) => write!(f, "Could not access csv file: {}", error),
+            CsvError::Csv(error) => write!(f, "Invalid csv data: {}", error),
+            CsvError::UnknownColumn(column) => write!(f, "Column {} not found", column),
+            CsvError::Parse {
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "Cannot parse '{}' in column {} on line {} as a number",
+                value, column, line
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<std::io::Error> for CsvError {
+    fn from(error: std::io::Error) -> Self {
+        CsvError::Io(error)
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(error: csv::Error) -> Self {
+        CsvError::Csv(error)
+    }
+}
+
+impl<T: Float> TrainingData<T> {
+    pub fn from_csv<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<Self, CsvError> {
+        TrainingData::read_csv(File::open(path)?, options)
+    }
+
+    pub fn read_csv<R: Read>(reader: R, options: &CsvOptions) -> Result<Self, CsvError> {
+        let mut records = ReaderBuilder::new()
+            .delimiter(options.delimiter)
+            .has_headers(false)
+            .trim(Trim::All)
+            .from_reader(reader)
+            .into_records();
+        let mut data = TrainingData {
+            inputs: vec![],
+            targets: vec![],
+        };
+
+        let first = match records.next() {
+            Some(first) => first?,
+            None => return Ok(data),
+        };
+        let has_header = match options.header {
+            CsvHeader::Present => true,
+            CsvHeader::Absent => false,
+            CsvHeader::Auto => first.iter().any(|cell| parse::<T>(cell).is_none()),
+        };
+        let names = if has_header {
+            first.iter().map(String::from).collect()
+        } else {
+            vec![]
+        };
+
+        let targets = resolve(&options.targets, &names, first.len())?;
+        let features = if options.features.is_empty() {
+            (0..first.len())
+                .filter(|column| !targets.contains(column))
+                .collect()
+        } else {
+            resolve(&options.features, &names, first.len())?
+        };
+
+        let first = if has_header { None } else { Some(Ok(first)) };
+        for record in first.into_iter().chain(records) {
+            let record = record?;
+            data.inputs.push(read_cells(&record, &features, &names)?);
+            data.targets.push(read_cells(&record, &targets, &names)?);
+        }
+
+        Ok(data)
+    }
+}
+
+// Writes one row per prediction, preceded by the header unless it is empty
+pub fn write_predictions<T: Float, W: Write>(
+    writer: W,
+    header: &[&str],
+    predictions: &[Vec<T>],
+    delimiter: u8,
+) -> Result<(), CsvError> {
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(writer);
+
+    if !header.is_empty() {
+        writer.write_record(header)?;
+    }
+    for prediction in predictions {
+        writer.write_record(prediction.iter().map(|x| x.to_string()))?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn parse<T: Float>(cell: &str) -> Option<T> {
+    T::from_str_radix(cell, 10).ok()
+}
+
+// Column positions for the selected columns, names are looked up in the header
+fn resolve(columns: &[Column], names: &[String], width: usize) -> Result<Vec<usize>, CsvError> {
+    columns
+        .iter()
+        .map(|column| {
+            let index = match column {
+                Column::Index(index) => Some(*index).filter(|index| *index < width),
+                Column::Name(name) => names.iter().position(|header| header == name),
+            };
+            index.ok_or_else(|| CsvError::UnknownColumn(column.clone()))
+        })
+        .collect()
+}
+
+fn read_cells<T: Float>(
+    record: &StringRecord,
+    columns: &[usize],
+    names: &[String],
+) -> Result<Vec<T>, CsvError> {
+    columns
+        .iter()
+        .map(|&index| {
+            parse(&record[index]).ok_or_else(|| CsvError::Parse {
+                line: record.position().map_or(0, |position| position.line()),
+                column: match names.get(index) {
+                    Some(name) => Column::Name(name.clone()),
+                    None => Column::Index(index),
+                },
+                value: record[index].to_string(),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn read_with_header() {
+    let csv = "age,height,label\n31, 1.8,1\n45,1.65,0\n";
+
+    let options = CsvOptions {
+        targets: vec!["label".into()],
+        ..CsvOptions::default()
+    };
+    let data: TrainingData = TrainingData::read_csv(csv.as_bytes(), &options).unwrap();
+    assert_eq!(data.inputs, vec![vec![31.0, 1.8], vec![45.0, 1.65]]);
+    assert_eq!(data.targets, vec![vec![1.0], vec![0.0]]);
+
+    let options = CsvOptions {
+        features: vec![Column::Index(1)],
+        targets: vec!["label".into(), "age".into()],
+        ..CsvOptions::default()
+    };
+    let data: TrainingData = TrainingData::read_csv(csv.as_bytes(), &options).unwrap();
+    assert_eq!(data.inputs, vec![vec![1.8], vec![1.65]]);
+    assert_eq!(data.targets, vec![vec![1.0, 31.0], vec![0.0, 45.0]]);
+}
+
+#[test]
+fn read_without_header() {
+    let csv = "1;2;3\n4;5;6\n";
+
+    let options = CsvOptions {
+        delimiter: b';',
+        targets: vec![2.into()],
+        ..CsvOptions::default()
+    };
+    let data: TrainingData<f32> = TrainingData::read_csv(csv.as_bytes(), &options).unwrap();
+    assert_eq!(data.inputs, vec![vec![1.0, 2.0], vec![4.0, 5.0]]);
+    assert_eq!(data.targets, vec![vec![3.0], vec![6.0]]);
+
+    // A numeric header row is only skipped when the header is declared
+    let options = CsvOptions {
+        delimiter: b';',
+        header: CsvHeader::Present,
+        ..CsvOptions::default()
+    };
+    let data: TrainingData = TrainingData::read_csv(csv.as_bytes(), &options).unwrap();
+    assert_eq!(data.inputs, vec![vec![4.0, 5.0, 6.0]]);
+    assert_eq!(data.targets, vec![Vec::<f64>::new()]);
+}
+
+#[test]
+fn read_errors() {
+    let csv = "x,y\n1,2\n3,oops\n";
+    let read = |targets: Vec<Column>| {
+        let options = CsvOptions {
+            targets,
+            ..CsvOptions::default()
+        };
+        TrainingData::<f64>::read_csv(csv.as_bytes(), &options)
+    };
+
+    match read(vec!["y".into()]) {
+        Err(CsvError::Parse {
+            line,
+            column,
+            value,
+        }) => {
+            assert_eq!(line, 3);
+            assert_eq!(column, Column::Name("y".to_string()));
+            assert_eq!(value, "oops");
+        }
+        other => panic!("Expected a parse error, got {:?}", other.map(|_| ())),
+    }
+    assert!(matches!(
+        read(vec!["z".into()]),
+        Err(CsvError::UnknownColumn(Column::Name(_)))
+    ));
+    assert!(matches!(
+        read(vec![2.into()]),
+        Err(CsvError::UnknownColumn(Column::Index(2)))
+    ));
+
+    let ragged = "1,2\n3\n";
+    assert!(matches!(
+        TrainingData::<f64>::read_csv(ragged.as_bytes(), &CsvOptions::default()),
+        Err(CsvError::Csv(_))
+    ));
+}
+
+#[test]
+fn prediction_round_trip() {
+    let path = std::env::temp_dir().join("neuralnet_predictions.csv");
+    let predictions = vec![vec![0.25, 1.0], vec![-3.5, 1e-7]];
+
+    write_predictions(
+        File::create(&path).unwrap(),
+        &["p", "q"],
+        &predictions,
+        b'\t',
+    )
+    .unwrap();
+    let options = CsvOptions {
+        delimiter: b'\t',
+        ..CsvOptions::default()
+    };
+    let data: TrainingData = TrainingData::from_csv(&path, &options).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(data.inputs, predictions);
+}
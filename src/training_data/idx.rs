@@ -0,0 +1,253 @@
+use std::{fs, io::Read, path::Path};
+
+use flate2::read::GzDecoder;
+
+use super::TrainingData;
+use crate::float::{self, Float};
+
+// IDX files start with two zero bytes, a type code and the number of dimensions. Only unsigned
+// bytes are supported, which is what MNIST and Fashion-MNIST use for both images and labels.
+const UNSIGNED_BYTE: u8 = 0x08;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Debug)]
+pub enum IdxError {
+    Io(std::io::Error),
+    Format(String),
+    UnsupportedType(u8),
+    CountMismatch { images: usize, labels: usize },
+}
+
+impl std::fmt::Display for IdxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IdxError::Io(error) => write!(f, "Could not read idx file: {}", error),
+            IdxError::Format(message) => write!(f, "Invalid idx data: {}", message),
+            IdxError::UnsupportedType(code) => {
+                write!(
+                    f,
+                    "Unsupported idx type 0x{:02x}, expected unsigned bytes",
+                    code
+                )
+            }
+            IdxError::CountMismatch { images, labels } => {
+                write!(f, "Found {} images but {} labels", images, labels)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IdxError {}
+
+impl From<std::io::Error> for IdxError {
+    fn from(error: std::io::Error) -> Self {
+        IdxError::Io(error)
+    }
+}
+
+struct Idx {
+    shape: Vec<usize>,
+    data: Vec<u8>,
+}
+
+impl<T: Float> TrainingData<T> {
+    // Either file may be gzip compressed. Pixels are scaled to [0, 1] and every label becomes a
+    // one-hot target of width classes, so training and test files get targets of the same width
+    // even when one of them lacks the largest label.
+    pub fn from_idx<P: AsRef<Path>, Q: AsRef<Path>>(
+        images: P,
+        labels: Q,
+        classes: usize,
+    ) -> Result<Self, IdxError> {
+        let images = read_idx(images.as_ref())?;
+        let labels = read_idx(labels.as_ref())?;
+
+        if images.shape.len() < 2 {
+            return Err(IdxError::Format(format!(
+                "images need at least 2 dimensions, found {}",
+                images.shape.len()
+            )));
+        }
+        if labels.shape.len() != 1 {
+            return Err(IdxError::Format(format!(
+                "labels need 1 dimension, found {}",
+                labels.shape.len()
+            )));
+        }
+        if images.shape[0] != labels.shape[0] {
+            return Err(IdxError::CountMismatch {
+                images: images.shape[0],
+                labels: labels.shape[0],
+            });
+        }
+
+        if let Some(label) = labels.data.iter().find(|label| **label as usize >= classes) {
+            return Err(IdxError::Format(format!(
+                "label {} is out of range for {} classes",
+                label, classes
+            )));
+        }
+
+        // read_idx already checked that the product of the whole shape fits
+        let pixels = images.shape[1..].iter().product::<usize>();
+        let scale = float::cast::<T>(u8::MAX as f64);
+        let inputs = (0..images.shape[0])
+            .map(|image| {
+                images.data[image * pixels..(image + 1) * pixels]
+                    .iter()
+                    .map(|pixel| float::cast::<T>(*pixel as f64) / scale)
+                    .collect()
+            })
+            .collect();
+
+        let targets = labels
+            .data
+            .iter()
+            .map(|label| {
+                let mut target = vec![T::zero(); classes];
+                target[*label as usize] = T::one();
+                target
+            })
+            .collect();
+
+        Ok(TrainingData { inputs, targets })
+    }
+}
+
+fn read_idx(path: &Path) -> Result<Idx, IdxError> {
+    let mut bytes = fs::read(path)?;
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut decompressed = vec![];
+        GzDecoder::new(&bytes[..]).read_to_end(&mut decompressed)?;
+        bytes = decompressed;
+    }
+
+    if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+        return Err(IdxError::Format("missing idx magic number".to_string()));
+    }
+    if bytes[2] != UNSIGNED_BYTE {
+        return Err(IdxError::UnsupportedType(bytes[2]));
+    }
+
+    let dimensions = bytes[3] as usize;
+    let header = 4 + 4 * dimensions;
+    if bytes.len() < header {
+        return Err(IdxError::Format(format!(
+            "header declares {} dimensions but the file ends first",
+            dimensions
+        )));
+    }
+    let shape = bytes[4..header]
+        .chunks_exact(4)
+        .map(|size| u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize)
+        .collect::<Vec<usize>>();
+
+    // Corrupt headers can declare shapes whose size does not fit in a usize
+    let expected = shape
+        .iter()
+        .try_fold(1usize, |size, dimension| size.checked_mul(*dimension))
+        .ok_or_else(|| IdxError::Format(format!("shape {:?} is too large", shape)))?;
+    if bytes.len() - header != expected {
+        return Err(IdxError::Format(format!(
+            "shape {:?} needs {} bytes of data, found {}",
+            shape,
+            expected,
+            bytes.len() - header
+        )));
+    }
+
+    bytes.drain(..header);
+    Ok(Idx { shape, data: bytes })
+}
+
+#[cfg(test)]
+fn idx_bytes(shape: &[u32], data: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0, 0, UNSIGNED_BYTE, shape.len() as u8];
+    for size in shape {
+        bytes.extend_from_slice(&size.to_be_bytes());
+    }
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+#[test]
+fn load_idx() {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let dir = std::env::temp_dir();
+    let images = dir.join("neuralnet_load_idx_images.idx3-ubyte");
+    let labels = dir.join("neuralnet_load_idx_labels.idx1-ubyte.gz");
+
+    // Three 2x2 images, the labels file is gzip compressed
+    fs::write(
+        &images,
+        idx_bytes(
+            &[3, 2, 2],
+            &[0, 255, 51, 0, 255, 255, 255, 255, 0, 0, 0, 102],
+        ),
+    )
+    .unwrap();
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(&idx_bytes(&[3], &[2, 0, 1])).unwrap();
+    fs::write(&labels, encoder.finish().unwrap()).unwrap();
+
+    let data: TrainingData = TrainingData::from_idx(&images, &labels, 4).unwrap();
+    fs::remove_file(&images).unwrap();
+    fs::remove_file(&labels).unwrap();
+
+    assert_eq!(data.inputs[0], vec![0.0, 1.0, 0.2, 0.0]);
+    assert_eq!(data.inputs[2], vec![0.0, 0.0, 0.0, 0.4]);
+    assert_eq!(
+        data.targets,
+        vec![
+            vec![0.0, 0.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0, 0.0]
+        ]
+    );
+}
+
+#[test]
+fn invalid_idx() {
+    let dir = std::env::temp_dir();
+    let images = dir.join("neuralnet_invalid_idx_images");
+    let labels = dir.join("neuralnet_invalid_idx_labels");
+    let load = |image_bytes: Vec<u8>, label_bytes: Vec<u8>| {
+        fs::write(&images, image_bytes).unwrap();
+        fs::write(&labels, label_bytes).unwrap();
+        TrainingData::<f32>::from_idx(&images, &labels, 3)
+    };
+
+    let truncated = load(idx_bytes(&[2, 2, 2], &[0; 7]), idx_bytes(&[2], &[0, 1]));
+    assert!(matches!(truncated, Err(IdxError::Format(_))));
+
+    let mismatch = load(idx_bytes(&[2, 2, 2], &[0; 8]), idx_bytes(&[3], &[0, 1, 2]));
+    assert!(matches!(
+        mismatch,
+        Err(IdxError::CountMismatch {
+            images: 2,
+            labels: 3
+        })
+    ));
+
+    let mut floats = idx_bytes(&[1], &[0, 0, 0, 0]);
+    floats[2] = 0x0d;
+    let unsupported = load(idx_bytes(&[1, 1, 1], &[0]), floats);
+    assert!(matches!(unsupported, Err(IdxError::UnsupportedType(0x0d))));
+
+    let out_of_range = load(idx_bytes(&[2, 1, 1], &[0; 2]), idx_bytes(&[2], &[0, 3]));
+    assert!(matches!(out_of_range, Err(IdxError::Format(_))));
+
+    let overflowing = load(
+        idx_bytes(&[u32::MAX, u32::MAX, u32::MAX], &[]),
+        idx_bytes(&[1], &[0]),
+    );
+    assert!(matches!(overflowing, Err(IdxError::Format(_))));
+
+    let missing = TrainingData::<f64>::from_idx(dir.join("neuralnet_no_such_file"), &labels, 3);
+    assert!(matches!(missing, Err(IdxError::Io(_))));
+
+    fs::remove_file(&images).unwrap();
+    fs::remove_file(&labels).unwrap();
+}
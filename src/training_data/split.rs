@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::{predicted_class, TrainingData};
+use crate::float::Float;
+
+// Yields (training, validation) pairs, fold i being the validation data of the i-th pair
+pub struct KFold<'a, T> {
+    data: &'a TrainingData<T>,
+    folds: Vec<Vec<usize>>,
+    next: usize,
+}
+
+impl<T: Float> Iterator for KFold<'_, T> {
+    type Item = (TrainingData<T>, TrainingData<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let validation = self.folds.get(self.next)?;
+        let training = self
+            .folds
+            .iter()
+            .enumerate()
+            .filter(|(fold, _)| *fold != self.next)
+            .flat_map(|(_, indices)| indices.iter().copied())
+            .collect::<Vec<usize>>();
+        self.next += 1;
+
+        Some((self.data.select(&training), self.data.select(validation)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.folds.len() - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: Float> ExactSizeIterator for KFold<'_, T> {}
+
+// Stratified variants keep the share of every class, the class of a sample being the index of its
+// largest target, or target >= 0.5 for single-target data
+impl<T: Float> TrainingData<T> {
+    // Shuffles the samples into one part per ratio, e.g. [0.8, 0.1, 0.1] for training,
+    // validation and test data
+    pub fn split<R: Rng + ?Sized>(&self, ratios: &[f64], rng: &mut R) -> Vec<TrainingData<T>> {
+        check_ratios(ratios);
+        let mut indices = (0..self.len()).collect::<Vec<usize>>();
+        indices.shuffle(rng);
+
+        partition(&indices, ratios)
+            .into_iter()
+            .map(|part| self.select(part))
+            .collect()
+    }
+
+    pub fn stratified_split<R: Rng + ?Sized>(
+        &self,
+        ratios: &[f64],
+        rng: &mut R,
+    ) -> Vec<TrainingData<T>> {
+        check_ratios(ratios);
+        let mut parts = vec![vec![]; ratios.len()];
+        for mut class in self.classes() {
+            class.shuffle(rng);
+            for (part, indices) in parts.iter_mut().zip(partition(&class, ratios)) {
+                part.extend_from_slice(indices);
+            }
+        }
+
+        // Otherwise every part would hold its samples grouped by class
+        parts
+            .into_iter()
+            .map(|mut part| {
+                part.shuffle(rng);
+                self.select(&part)
+            })
+            .collect()
+    }
+
+    pub fn k_fold<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> KFold<'_, T> {
+        self.check_folds(k);
+        let mut indices = (0..self.len()).collect::<Vec<usize>>();
+        indices.shuffle(rng);
+
+        let n = indices.len();
+        KFold {
+            data: self,
+            folds: (0..k)
+                .map(|fold| indices[fold * n / k..(fold + 1) * n / k].to_vec())
+                .collect(),
+            next: 0,
+        }
+    }
+
+    pub fn stratified_k_fold<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> KFold<'_, T> {
+        self.check_folds(k);
+        let mut folds = vec![vec![]; k];
+        let mut position = 0;
+
+        // Dealing the samples of each class round-robin keeps the folds within one sample of
+        // each other, both in size and per class
+        for mut class in self.classes() {
+            class.shuffle(rng);
+            for index in class {
+                folds[position % k].push(index);
+                position += 1;
+            }
+        }
+
+        KFold {
+            data: self,
+            folds,
+            next: 0,
+        }
+    }
+
+    fn select(&self, indices: &[usize]) -> TrainingData<T> {
+        TrainingData {
+            inputs: indices.iter().map(|i| self.inputs[*i].clone()).collect(),
+            targets: indices.iter().map(|i| self.targets[*i].clone()).collect(),
+        }
+    }
+
+    // Sample indices grouped by class, in class order
+    fn classes(&self) -> Vec<Vec<usize>> {
+        let mut classes = BTreeMap::<usize, Vec<usize>>::new();
+        for (index, target) in self.targets.iter().enumerate() {
+            classes
+                .entry(predicted_class(target))
+                .or_default()
+                .push(index);
+        }
+        classes.into_values().collect()
+    }
+
+    fn check_folds(&self, k: usize) {
+        if k < 2 || k > self.len() {
+            panic!(
+                "Cannot make {} folds out of {} samples, k must be between 2 and the sample count",
+                k,
+                self.len()
+            );
+        }
+    }
+}
+
+fn check_ratios(ratios: &[f64]) {
+    if ratios.is_empty()
+        || ratios.iter().any(|ratio| *ratio <= 0.0)
+        || (ratios.iter().sum::<f64>() - 1.0).abs() > 1e-9
+    {
+        panic!("Split ratios {:?} must be positive and sum to 1", ratios);
+    }
+}
+
+// Consecutive slices sized by the ratios, with boundaries rounded so the parts cover every index
+fn partition<'a>(indices: &'a [usize], ratios: &[f64]) -> Vec<&'a [usize]> {
+    let mut parts = Vec::with_capacity(ratios.len());
+    let mut start = 0;
+    let mut cumulative = 0.0;
+
+    for (i, ratio) in ratios.iter().enumerate() {
+        cumulative += ratio;
+        let end = if i == ratios.len() - 1 {
+            indices.len()
+        } else {
+            ((cumulative * indices.len() as f64).round() as usize).clamp(start, indices.len())
+        };
+        parts.push(&indices[start..end]);
+        start = end;
+    }
+
+    parts
+}
+
+#[cfg(test)]
+fn labelled(count: usize, positive_every: usize) -> TrainingData {
+    let inputs = (0..count).map(|i| vec![i as f64]).collect();
+    let targets = (0..count)
+        .map(|i| vec![i.is_multiple_of(positive_every) as usize as f64])
+        .collect();
+    TrainingData::new(&inputs, &targets)
+}
+
+#[cfg(test)]
+fn positives(data: &TrainingData) -> usize {
+    data.targets
+        .iter()
+        .filter(|target| target[0] == 1.0)
+        .count()
+}
+
+#[test]
+fn splits() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let data = labelled(100, 5);
+    let mut rng = StdRng::seed_from_u64(17);
+
+    let parts = data.split(&[0.7, 0.2, 0.1], &mut rng);
+    assert_eq!(
+        parts.iter().map(|part| part.len()).collect::<Vec<usize>>(),
+        vec![70, 20, 10]
+    );
+    let mut seen = parts
+        .iter()
+        .flat_map(|part| part.inputs.iter().map(|input| input[0] as usize))
+        .collect::<Vec<usize>>();
+    seen.sort();
+    assert_eq!(seen, (0..100).collect::<Vec<usize>>());
+    // Inputs and targets stay paired
+    assert!(parts[0]
+        .inputs
+        .iter()
+        .zip(&parts[0].targets)
+        .all(|(input, target)| (input[0] as usize).is_multiple_of(5) == (target[0] == 1.0)));
+
+    // 20 positives split exactly 14 / 4 / 2
+    let parts = data.stratified_split(&[0.7, 0.2, 0.1], &mut rng);
+    assert_eq!(
+        parts.iter().map(positives).collect::<Vec<usize>>(),
+        vec![14, 4, 2]
+    );
+    assert_eq!(
+        parts.iter().map(|part| part.len()).collect::<Vec<usize>>(),
+        vec![70, 20, 10]
+    );
+}
+
+#[test]
+fn k_folds() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let data = labelled(23, 4);
+    let mut rng = StdRng::seed_from_u64(2);
+
+    let folds = data.k_fold(5, &mut rng).collect::<Vec<_>>();
+    assert_eq!(folds.len(), 5);
+    let mut validated = vec![];
+    for (training, validation) in &folds {
+        assert_eq!(training.len() + validation.len(), 23);
+        assert!((4..=5).contains(&validation.len()));
+        assert!(!training
+            .inputs
+            .iter()
+            .any(|input| validation.inputs.contains(input)));
+        validated.extend(validation.inputs.iter().map(|input| input[0] as usize));
+    }
+    validated.sort();
+    assert_eq!(validated, (0..23).collect::<Vec<usize>>());
+
+    // 6 positives over 5 folds, so one fold gets 2 and the others 1
+    let mut counts = data
+        .stratified_k_fold(5, &mut rng)
+        .map(|(_, validation)| positives(&validation))
+        .collect::<Vec<usize>>();
+    counts.sort();
+    assert_eq!(counts, vec![1, 1, 1, 1, 2]);
+}
+
+#[test]
+#[should_panic]
+fn invalid_ratios() {
+    labelled(10, 2).split(&[0.5, 0.6], &mut rand::thread_rng());
+}
@@ -0,0 +1,153 @@
+use crate::{
+    float::Float,
+    network::{Network, TrainingError},
+    training_data::{predicted_class, KFold, TrainingData},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Evaluation {
+    // Mean squared error over every output of every sample
+    pub loss: f64,
+    // Fraction of samples whose predicted class matches the class of their target
+    pub accuracy: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossValidation {
+    // Validation metrics of each fold, in fold order
+    pub folds: Vec<Evaluation>,
+}
+
+impl CrossValidation {
+    pub fn mean(&self) -> Evaluation {
+        let count = self.folds.len() as f64;
+        Evaluation {
+            loss: self.folds.iter().map(|fold| fold.loss).sum::<f64>() / count,
+            accuracy: self.folds.iter().map(|fold| fold.accuracy).sum::<f64>() / count,
+        }
+    }
+
+    // Population standard deviation across the folds
+    pub fn std_dev(&self) -> Evaluation {
+        let mean = self.mean();
+        let count = self.folds.len() as f64;
+        let deviation = |metric: &dyn Fn(&Evaluation) -> f64, mean: f64| {
+            let variance = self
+                .folds
+                .iter()
+                .map(|fold| (metric(fold) - mean).powi(2))
+                .sum::<f64>()
+                / count;
+            variance.sqrt()
+        };
+
+        Evaluation {
+            loss: deviation(&|fold| fold.loss, mean.loss),
+            accuracy: deviation(&|fold| fold.accuracy, mean.accuracy),
+        }
+    }
+}
+
+impl<T: Float> Network<T> {
+    pub fn evaluate(&mut self, data: &TrainingData<T>) -> Evaluation {
+        if data.is_empty() {
+            panic!("Evaluation requires at least one sample");
+        }
+
+        let mut squared_error = 0.0;
+        let mut outputs_count = 0;
+        let mut correct = 0;
+
+        for (input, target) in data.inputs.iter().zip(&data.targets) {
+            let output = self.feed_forward(input.clone());
+            for (output, target) in output.iter().zip(target) {
+                squared_error += (*target - *output).powi(2).to_f64().unwrap();
+                outputs_count += 1;
+            }
+            if predicted_class(&output) == predicted_class(target) {
+                correct += 1;
+            }
+        }
+
+        Evaluation {
+            loss: squared_error / outputs_count as f64,
+            accuracy: correct as f64 / data.len() as f64,
+        }
+    }
+}
+
+// Trains a fresh network from build on the training data of every fold and evaluates it on the
+// fold's validation data. Stops at the first fold whose training fails.
+pub fn cross_validate<T, F>(
+    folds: KFold<'_, T>,
+    epochs: u16,
+    mut build: F,
+) -> Result<CrossValidation, TrainingError>
+where
+    T: Float,
+    F: FnMut() -> Network<T>,
+{
+    let mut evaluations = Vec::with_capacity(folds.len());
+
+    for (training, validation) in folds {
+        let mut network = build();
+        network.train(training.inputs, training.targets, epochs)?;
+        evaluations.push(network.evaluate(&validation));
+    }
+
+    Ok(CrossValidation { folds: evaluations })
+}
+
+#[test]
+fn cross_validation() {
+    use crate::activation::SIGMOID;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Samples above 0.5 are positive
+    let inputs = (0..20).map(|i| vec![i as f64 / 19.0]).collect();
+    let targets = (0..20).map(|i| vec![(i >= 10) as usize as f64]).collect();
+    let data = TrainingData::new(&inputs, &targets);
+
+    let mut seed = 0;
+    let folds = data.stratified_k_fold(4, &mut StdRng::seed_from_u64(9));
+    let result = cross_validate(folds, 1000, || {
+        seed += 1;
+        Network::with_seed(vec![1, 3, 1], SIGMOID, 1.0, seed)
+    })
+    .unwrap();
+
+    assert_eq!(seed, 4);
+    assert_eq!(result.folds.len(), 4);
+    assert!(result.mean().accuracy >= 0.9);
+    assert!(result.mean().loss < 0.1);
+    assert!(result.std_dev().loss >= 0.0);
+}
+
+#[test]
+#[should_panic]
+fn evaluate_empty() {
+    use crate::activation::SIGMOID;
+
+    let mut network = Network::new(vec![1, 2, 1], SIGMOID, 0.5);
+    network.evaluate(&TrainingData::new(&vec![], &vec![]));
+}
+
+#[test]
+fn cross_validation_metrics() {
+    let result = CrossValidation {
+        folds: vec![
+            Evaluation {
+                loss: 0.1,
+                accuracy: 1.0,
+            },
+            Evaluation {
+                loss: 0.3,
+                accuracy: 0.5,
+            },
+        ],
+    };
+
+    let (mean, std_dev) = (result.mean(), result.std_dev());
+    assert!((mean.loss - 0.2).abs() < 1e-12 && (mean.accuracy - 0.75).abs() < 1e-12);
+    assert!((std_dev.loss - 0.1).abs() < 1e-12 && (std_dev.accuracy - 0.25).abs() < 1e-12);
+}